@@ -1,58 +1,342 @@
-use near_sdk::{near, AccountId, BorshStorageKey};
+use std::collections::HashSet;
+
+use near_sdk::{env, env::panic_str, near, AccountId, BorshStorageKey};
 use near_sdk_contract_tools::owner::Owner;
 use near_sdk_contract_tools::pause::Pause;
 use near_sdk_contract_tools::rbac::Rbac;
 
-use crate::{Contract, ContractExt};
+use crate::{common::now, event::LtipEvent, Contract, ContractExt};
 
-#[derive(BorshStorageKey)]
+#[derive(BorshStorageKey, Clone, PartialEq, Eq, Hash)]
 #[near(serializers = [json, borsh])]
 #[serde(rename_all = "snake_case")]
 pub enum Role {
     Issuer,
     Executor,
     Predecessor,
+    /// Can extend (never shorten) a grant's lockup via `GrantApi::update_lockup`, following the
+    /// Solana stake `LockupArgs` custodian model.
+    Custodian,
+}
+
+/// A single mutating surface that the owner can freeze independently of the global `Pause`
+/// switch, e.g. halting payouts during an incident while still allowing issuance.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Buy,
+    Issue,
+    Terminate,
+    Migrate,
+    CreateGrant,
 }
 
 /// AuthApi exposes helper methods for managing role assignments on the contract.
 pub trait AuthApi {
-    /// Grants the specified `role` to `account_id`.
+    /// Grants the specified `role` to `account_id`. Once `MultisigApi::configure_multisig` has
+    /// set a non-zero `multisig_threshold`, this direct shortcut is disabled and panics —
+    /// `MultisigApi::propose_grant_role`/`approve`/`execute` is the only way in, so a single
+    /// compromised owner key can no longer unilaterally grant itself privilege.
     fn grant_role(&mut self, account_id: &AccountId, role: Role);
-    /// Revokes the specified `role` from `account_id`.
+    /// Grants `role` to `account_id` until `expires_at` (seconds since epoch, same convention as
+    /// `Grant`'s own timestamps). `has_role` and `Contract::require_role_active` treat the
+    /// assignment as absent once `now()` passes `expires_at`, without anything having to revoke
+    /// it — though the RBAC entry itself isn't removed until `sweep_expired` reclaims it. Disabled
+    /// (panics) once `multisig_threshold` is non-zero, same as `grant_role`.
+    fn grant_role_until(&mut self, account_id: &AccountId, role: Role, expires_at: u32);
+    /// Revokes the specified `role` from `account_id`. Disabled (panics) once
+    /// `multisig_threshold` is non-zero; use `MultisigApi::propose_revoke_role` instead.
     fn revoke_role(&mut self, account_id: &AccountId, role: Role);
-    /// Returns true when `account_id` currently holds `role`.
+    /// Returns true when `account_id` currently holds `role` and, if `grant_role_until` recorded
+    /// an expiry for it, that expiry hasn't yet passed.
     fn has_role(&self, account_id: &AccountId, role: Role) -> bool;
+    /// Removes up to `limit` RBAC assignments of `role` whose `grant_role_until` expiry has
+    /// passed, emitting `LtipEvent::RoleRevoked` for each so the audit trail reflects the
+    /// expiry exactly as an explicit `revoke_role` would. Returns the number actually removed.
+    fn sweep_expired(&mut self, role: Role, limit: u32) -> u32;
     /// Returns list of accounts for the provided `Role`.
     fn members(&self, role: Role) -> Vec<AccountId>;
+    /// Returns the number of accounts holding `role`, for sizing a `members_paged` walk without
+    /// collecting the whole set.
+    fn member_count(&self, role: Role) -> u32;
+    /// Returns up to `limit` accounts holding `role`, skipping the first `offset`. Lets a caller
+    /// page through a role with more members than fit in one `members` call; returns fewer than
+    /// `limit` once the range runs past the end (or an empty `Vec` once `offset` does).
+    fn members_paged(&self, role: Role, offset: u32, limit: u32) -> Vec<AccountId>;
+    /// Clears the contract-wide pause. Disabled (panics) once `multisig_threshold` is non-zero;
+    /// use `MultisigApi::propose_force_unpause` instead.
     fn force_unpause(&mut self);
+
+    /// Permanently stops accepting `FtMessage::Migrate` batches. One-way; cannot be undone.
+    fn finalize_migration(&mut self);
+
+    /// Freezes a single `Operation` without invoking the global `Pause` switch.
+    fn pause_operation(&mut self, operation: Operation);
+    /// Unfreezes a single `Operation` previously paused with `pause_operation`.
+    fn unpause_operation(&mut self, operation: Operation);
+    /// Returns the `Operation`s currently frozen via `pause_operation`.
+    fn get_paused_operations(&self) -> Vec<Operation>;
+
+    /// Grants `role` to `account_id` within `scope_id` only (e.g. one incentive program out of
+    /// several this contract might manage), leaving any global assignment untouched. Disabled
+    /// (panics) once `multisig_threshold` is non-zero, same as `grant_role`.
+    fn grant_role_in_scope(&mut self, account_id: &AccountId, role: Role, scope_id: String);
+    /// Revokes a scoped assignment previously granted by `grant_role_in_scope`. A no-op if
+    /// `account_id` never held `role` in `scope_id`; leaves a global assignment untouched.
+    /// Disabled (panics) once `multisig_threshold` is non-zero, same as `revoke_role`.
+    fn revoke_role_in_scope(&mut self, account_id: &AccountId, role: Role, scope_id: String);
+    /// Returns true when `account_id` holds `role` in `scope_id` — either via a matching
+    /// `grant_role_in_scope` entry, or via `has_role`, which acts as an implicit grant in every
+    /// scope.
+    fn has_role_in_scope(&self, account_id: &AccountId, role: Role, scope_id: String) -> bool;
+    /// Returns every account holding `role` in `scope_id`, unioning accounts scoped to it with
+    /// the role's global `members` (who hold it in every scope).
+    fn members_in_scope(&self, role: Role, scope_id: String) -> Vec<AccountId>;
 }
 
 #[near]
 impl AuthApi for Contract {
     fn grant_role(&mut self, account_id: &AccountId, role: Role) {
         Self::require_owner();
+        self.require_multisig_not_configured();
+
+        self.add_role(account_id, &role);
+        self.commit_event(&LtipEvent::RoleGranted((account_id.clone(), role)));
+    }
+
+    fn grant_role_until(&mut self, account_id: &AccountId, role: Role, expires_at: u32) {
+        Self::require_owner();
+        self.require_multisig_not_configured();
 
         self.add_role(account_id, &role);
+        self.role_expirations
+            .insert((account_id.clone(), role.clone()), expires_at);
+        self.commit_event(&LtipEvent::RoleGranted((account_id.clone(), role)));
     }
 
     fn revoke_role(&mut self, account_id: &AccountId, role: Role) {
         Self::require_owner();
+        self.require_multisig_not_configured();
 
         self.remove_role(account_id, &role);
+        self.role_expirations
+            .remove(&(account_id.clone(), role.clone()));
+        self.commit_event(&LtipEvent::RoleRevoked((account_id.clone(), role)));
     }
 
     fn has_role(&self, account_id: &AccountId, role: Role) -> bool {
-        <Self as Rbac>::has_role(account_id, &role)
+        if !<Self as Rbac>::has_role(account_id, &role) {
+            return false;
+        }
+
+        match self.role_expirations.get(&(account_id.clone(), role)) {
+            Some(expires_at) => now() <= *expires_at,
+            None => true,
+        }
+    }
+
+    fn sweep_expired(&mut self, role: Role, limit: u32) -> u32 {
+        Self::require_owner();
+
+        let current_time = now();
+        let expired: Vec<AccountId> = self
+            .role_expirations
+            .iter()
+            .filter(|((_, entry_role), expires_at)| {
+                *entry_role == role && current_time > **expires_at
+            })
+            .map(|((account_id, _), _)| account_id.clone())
+            .take(limit as usize)
+            .collect();
+
+        for account_id in &expired {
+            self.remove_role(account_id, &role);
+            self.role_expirations
+                .remove(&(account_id.clone(), role.clone()));
+            self.commit_event(&LtipEvent::RoleRevoked((account_id.clone(), role.clone())));
+        }
+
+        expired.len() as u32
     }
 
     fn members(&self, role: Role) -> Vec<AccountId> {
         Self::iter_members_of(&role).collect()
     }
 
+    fn member_count(&self, role: Role) -> u32 {
+        Self::iter_members_of(&role).count() as u32
+    }
+
+    fn members_paged(&self, role: Role, offset: u32, limit: u32) -> Vec<AccountId> {
+        Self::iter_members_of(&role)
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
     fn force_unpause(&mut self) {
         Self::require_owner();
+        self.require_multisig_not_configured();
 
         self.unpause();
+        self.commit_event(&LtipEvent::ForceUnpaused(env::predecessor_account_id()));
+    }
+
+    fn finalize_migration(&mut self) {
+        Self::require_owner();
+
+        self.migration_finalized = true;
+    }
+
+    fn pause_operation(&mut self, operation: Operation) {
+        Self::require_owner();
+
+        self.paused_operations.insert(operation, true);
+    }
+
+    fn unpause_operation(&mut self, operation: Operation) {
+        Self::require_owner();
+
+        self.paused_operations.remove(&operation);
+    }
+
+    fn get_paused_operations(&self) -> Vec<Operation> {
+        self.paused_operations.keys().copied().collect()
+    }
+
+    fn grant_role_in_scope(&mut self, account_id: &AccountId, role: Role, scope_id: String) {
+        Self::require_owner();
+        self.require_multisig_not_configured();
+
+        self.scoped_roles
+            .insert((scope_id.clone(), role.clone(), account_id.clone()));
+        self.commit_event(&LtipEvent::RoleGrantedInScope((
+            account_id.clone(),
+            role,
+            scope_id,
+        )));
+    }
+
+    fn revoke_role_in_scope(&mut self, account_id: &AccountId, role: Role, scope_id: String) {
+        Self::require_owner();
+        self.require_multisig_not_configured();
+
+        self.scoped_roles
+            .remove(&(scope_id.clone(), role.clone(), account_id.clone()));
+        self.commit_event(&LtipEvent::RoleRevokedInScope((
+            account_id.clone(),
+            role,
+            scope_id,
+        )));
+    }
+
+    fn has_role_in_scope(&self, account_id: &AccountId, role: Role, scope_id: String) -> bool {
+        self.has_role(account_id, role.clone())
+            || self
+                .scoped_roles
+                .contains(&(scope_id, role, account_id.clone()))
+    }
+
+    fn members_in_scope(&self, role: Role, scope_id: String) -> Vec<AccountId> {
+        let mut members = self.members(role.clone());
+
+        for (scope, scoped_role, account_id) in &self.scoped_roles {
+            if *scope == scope_id && *scoped_role == role && !members.contains(account_id) {
+                members.push(account_id.clone());
+            }
+        }
+
+        members
+    }
+}
+
+impl Contract {
+    /// Panics if `MultisigApi::configure_multisig` has set a non-zero `multisig_threshold`,
+    /// which means `grant_role`/`revoke_role`/`force_unpause`'s direct owner-gated shortcut has
+    /// been superseded by the approval queue (`propose_grant_role`/`propose_revoke_role`/
+    /// `propose_force_unpause` followed by `approve`/`execute`).
+    pub(crate) fn require_multisig_not_configured(&self) {
+        if self.multisig_threshold > 0 {
+            panic_str(
+                "Multisig is configured; use MultisigApi's propose/approve/execute flow instead",
+            );
+        }
+    }
+
+    /// Panics unless `operation` is neither globally paused nor individually frozen via
+    /// `pause_operation`.
+    pub(crate) fn require_operation_unpaused(&self, operation: Operation) {
+        Self::require_unpaused();
+
+        if *self.paused_operations.get(&operation).unwrap_or(&false) {
+            panic_str("Operation is currently paused");
+        }
+    }
+
+    /// The role-gated entry points' usual `Self::require_role(&Role::X)` check, additionally
+    /// honoring `grant_role_until`: if the caller's assignment has an expiry and it's passed,
+    /// this panics exactly as if the caller never had the role, even though RBAC storage (and
+    /// thus `Self::require_role` alone) still says otherwise until `sweep_expired` reclaims it.
+    pub(crate) fn require_role_active(&self, role: &Role) {
+        Self::require_role(role);
+
+        let caller = env::predecessor_account_id();
+        if let Some(expires_at) = self.role_expirations.get(&(caller, role.clone())) {
+            if now() > *expires_at {
+                panic_str("Role assignment has expired");
+            }
+        }
+    }
+
+    /// Invoked by `InitApi::migrate` after an upgrade to purge RBAC assignments for roles the
+    /// new code no longer lists in `still_valid`. A removed or renamed `Role` variant can't be
+    /// named by this binary any more than by its callers, so there's no way to literally iterate
+    /// "discriminants no longer defined" — instead this walks every variant the *current* build
+    /// still compiles and treats any one `still_valid` omits as the retired one, revoking its
+    /// members exactly as `revoke_role` would and returning how many were cleaned up.
+    pub(crate) fn revoke_orphaned_roles(&mut self, still_valid: &HashSet<Role>) -> u32 {
+        let all_roles = [
+            Role::Issuer,
+            Role::Executor,
+            Role::Predecessor,
+            Role::Custodian,
+        ];
+
+        let mut removed = 0u32;
+        for role in all_roles {
+            if still_valid.contains(&role) {
+                continue;
+            }
+
+            let members: Vec<AccountId> = Self::iter_members_of(&role).collect();
+            for account_id in members {
+                self.remove_role(&account_id, &role);
+                self.role_expirations
+                    .remove(&(account_id.clone(), role.clone()));
+                self.commit_event(&LtipEvent::RoleRevoked((account_id, role.clone())));
+                removed += 1;
+            }
+
+            let scoped: Vec<(String, AccountId)> = self
+                .scoped_roles
+                .iter()
+                .filter(|(_, scoped_role, _)| *scoped_role == role)
+                .map(|(scope_id, _, account_id)| (scope_id.clone(), account_id.clone()))
+                .collect();
+            for (scope_id, account_id) in scoped {
+                self.scoped_roles
+                    .remove(&(scope_id.clone(), role.clone(), account_id.clone()));
+                self.commit_event(&LtipEvent::RoleRevokedInScope((
+                    account_id,
+                    role.clone(),
+                    scope_id,
+                )));
+                removed += 1;
+            }
+        }
+
+        removed
     }
 }
 
@@ -60,11 +344,13 @@ impl AuthApi for Contract {
 mod tests {
     use std::panic::{self, AssertUnwindSafe};
 
-    use near_sdk::AccountId;
+    use near_sdk::{AccountId, NearToken};
     use rstest::*;
 
     use crate::{
-        auth::{AuthApi, Role},
+        auth::{AuthApi, Operation, Role},
+        event::AuditApi,
+        grant::GrantApi,
         tests::context::TestContext,
         tests::fixtures::*,
         Contract,
@@ -145,6 +431,68 @@ mod tests {
         assert_eq!(members.len(), 3);
     }
 
+    #[rstest]
+    fn member_count_matches_members_len(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role(&alice, Role::Issuer);
+        contract.grant_role(&bob, Role::Issuer);
+
+        // Contract fixture grants Issuer role to issuer, and we grant it to alice and bob
+        assert_eq!(contract.member_count(Role::Issuer), 3);
+        assert_eq!(contract.members(Role::Issuer).len(), 3);
+    }
+
+    #[rstest]
+    fn members_paged_walks_the_full_set_without_duplicates_or_gaps(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        issuer: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role(&alice, Role::Issuer);
+        contract.grant_role(&bob, Role::Issuer);
+
+        let mut all = contract.members(Role::Issuer);
+        all.sort();
+
+        let mut paged = Vec::new();
+        paged.extend(contract.members_paged(Role::Issuer, 0, 2));
+        paged.extend(contract.members_paged(Role::Issuer, 2, 2));
+        paged.sort();
+
+        assert_eq!(paged, all);
+        assert!(paged.contains(&issuer));
+        assert!(paged.contains(&alice));
+        assert!(paged.contains(&bob));
+    }
+
+    #[rstest]
+    fn members_paged_returns_fewer_once_the_range_runs_past_the_end(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role(&alice, Role::Issuer);
+
+        let count = contract.member_count(Role::Issuer);
+        let page = contract.members_paged(Role::Issuer, count - 1, 10);
+        assert_eq!(page.len(), 1);
+
+        let empty_page = contract.members_paged(Role::Issuer, count, 10);
+        assert!(empty_page.is_empty());
+    }
+
     #[rstest]
     fn non_owner_with_role_cannot_grant_roles(
         mut context: TestContext,
@@ -186,4 +534,258 @@ mod tests {
         assert!(result.is_err());
         assert!(contract.has_role(&issuer, Role::Issuer));
     }
+
+    #[rstest]
+    fn pause_operation_blocks_only_that_operation(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        contract.spare_balance = 10_000.into();
+
+        context.switch_account(&owner);
+        contract.pause_operation(Operation::Issue);
+        assert_eq!(contract.get_paused_operations(), vec![Operation::Issue]);
+
+        context.switch_to_issuer();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.issue(1_000, vec![(alice.clone(), 1_000.into(), None)], None);
+        }));
+        assert!(result.is_err());
+
+        context.switch_to_executor();
+        // Terminate isn't paused, so it proceeds past the pause check (no grant to terminate is a no-op).
+        contract.terminate(alice.clone(), 2_000);
+
+        context.switch_account(&owner);
+        contract.unpause_operation(Operation::Issue);
+        assert!(contract.get_paused_operations().is_empty());
+
+        context.switch_to_issuer();
+        context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.issue(1_000, vec![(alice.clone(), 1_000.into(), None)], None);
+        });
+        assert!(contract.accounts.get(&alice).is_some());
+    }
+
+    #[rstest]
+    fn grant_role_commits_an_audit_event(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        let (initial_sequence, _) = contract.get_audit_head();
+
+        context.switch_account(&owner);
+        contract.grant_role(&alice, Role::Executor);
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, initial_sequence + 1);
+    }
+
+    #[rstest]
+    fn revoke_role_commits_an_audit_event(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role(&alice, Role::Executor);
+        let (sequence_after_grant, _) = contract.get_audit_head();
+
+        contract.revoke_role(&alice, Role::Executor);
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, sequence_after_grant + 1);
+    }
+
+    #[rstest]
+    fn force_unpause_commits_an_audit_event(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+    ) {
+        near_sdk_contract_tools::pause::Pause::pause(&mut contract);
+        let (initial_sequence, _) = contract.get_audit_head();
+
+        context.switch_account(&owner);
+        contract.force_unpause();
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, initial_sequence + 1);
+    }
+
+    #[rstest]
+    fn grant_role_until_expires_after_the_given_timestamp(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role_until(&alice, Role::Executor, 1_000);
+
+        context.set_block_timestamp_in_seconds(500);
+        assert!(contract.has_role(&alice, Role::Executor));
+
+        context.set_block_timestamp_in_seconds(1_001);
+        assert!(!contract.has_role(&alice, Role::Executor));
+    }
+
+    #[rstest]
+    fn require_role_active_panics_once_expired(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role_until(&alice, Role::Executor, 1_000);
+
+        context.switch_account(&alice);
+        context.set_block_timestamp_in_seconds(1_001);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.terminate(alice.clone(), 2_000);
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn sweep_expired_removes_the_rbac_entry_and_commits_a_revoke_event(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role_until(&alice, Role::Executor, 1_000);
+        let (sequence_after_grant, _) = contract.get_audit_head();
+
+        context.set_block_timestamp_in_seconds(1_001);
+        context.switch_account(&owner);
+        let swept = contract.sweep_expired(Role::Executor, 10);
+
+        assert_eq!(swept, 1);
+        assert!(!contract.has_role(&alice, Role::Executor));
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, sequence_after_grant + 1);
+    }
+
+    #[rstest]
+    fn sweep_expired_leaves_unexpired_assignments_alone(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role_until(&alice, Role::Executor, 1_000);
+
+        context.set_block_timestamp_in_seconds(500);
+        let swept = contract.sweep_expired(Role::Executor, 10);
+
+        assert_eq!(swept, 0);
+        assert!(contract.has_role(&alice, Role::Executor));
+    }
+
+    #[rstest]
+    fn pause_operation_requires_owner(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.pause_operation(Operation::Buy);
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.get_paused_operations().is_empty());
+    }
+
+    #[rstest]
+    fn has_role_in_scope_is_true_for_a_matching_scoped_grant(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role_in_scope(&alice, Role::Issuer, "program-a".to_string());
+
+        assert!(contract.has_role_in_scope(&alice, Role::Issuer, "program-a".to_string()));
+        assert!(!contract.has_role_in_scope(&alice, Role::Issuer, "program-b".to_string()));
+    }
+
+    #[rstest]
+    fn has_role_in_scope_treats_a_global_grant_as_every_scope(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role(&alice, Role::Issuer);
+
+        assert!(contract.has_role_in_scope(&alice, Role::Issuer, "program-a".to_string()));
+        assert!(contract.has_role_in_scope(&alice, Role::Issuer, "program-b".to_string()));
+    }
+
+    #[rstest]
+    fn revoke_role_in_scope_leaves_other_scopes_untouched(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role_in_scope(&alice, Role::Issuer, "program-a".to_string());
+        contract.grant_role_in_scope(&alice, Role::Issuer, "program-b".to_string());
+
+        contract.revoke_role_in_scope(&alice, Role::Issuer, "program-a".to_string());
+
+        assert!(!contract.has_role_in_scope(&alice, Role::Issuer, "program-a".to_string()));
+        assert!(contract.has_role_in_scope(&alice, Role::Issuer, "program-b".to_string()));
+    }
+
+    #[rstest]
+    fn members_in_scope_unions_scoped_and_global_grants(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        issuer: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.grant_role_in_scope(&alice, Role::Issuer, "program-a".to_string());
+
+        let mut members = contract.members_in_scope(Role::Issuer, "program-a".to_string());
+        members.sort();
+
+        // Contract fixture grants Issuer role globally to `issuer`, which counts in every scope.
+        assert!(members.contains(&issuer));
+        assert!(members.contains(&alice));
+        assert_eq!(members.len(), 2);
+    }
+
+    #[rstest]
+    fn grant_role_in_scope_requires_owner(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.grant_role_in_scope(&bob, Role::Issuer, "program-a".to_string());
+        }));
+
+        assert!(result.is_err());
+        assert!(!contract.has_role_in_scope(&bob, Role::Issuer, "program-a".to_string()));
+    }
 }
@@ -0,0 +1,950 @@
+use std::{cmp, collections::HashSet};
+
+use near_sdk::{env, json_types::U128, near, require, serde_json, AccountId, NearToken, Promise};
+use near_sdk_contract_tools::{ft::nep141::GAS_FOR_FT_TRANSFER_CALL, rbac::Rbac};
+
+use crate::{
+    auth::Operation,
+    common::{assert_nonzero_amount, GasBudget},
+    grant::TransferKey,
+    Contract, ContractExt, Role,
+};
+
+/// Reserved for the `on_authorize_complete` callback a `match_buy_orders` fill batch is
+/// `.then()`-chained to, same as `authorize`'s own transfer batch.
+const GAS_FOR_RESOLVE_TRANSFER: near_sdk::Gas = near_sdk::Gas::from_tgas(5);
+
+/// A resting buyback bid, priced in basis points of `order_amount` (tokens paid per unit sold),
+/// posted by the executor and matched against grantees' outstanding `order_amount`s by
+/// `match_buy_orders`.
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct BuyOrder {
+    pub order_id: u64,
+    /// Who posted this bid, kept for audit only — the NEAR it pays out of is `spare_balance`,
+    /// reserved up front by `post_buy_order` and refunded by `cancel_buy`.
+    pub bidder: AccountId,
+    pub price_bps: u32,
+    pub remaining_amount: U128,
+}
+
+/// A resting ask, posted by a grant holder via `place_sell_order` against a specific grant's
+/// `order_amount` (the sell side of the book), matched against `buy_orders` by
+/// `match_sell_orders`.
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct SellOrder {
+    pub order_id: u64,
+    pub account_id: AccountId,
+    pub issue_at: u32,
+    /// The minimum basis-point rate the holder will accept, same units as `BuyOrder::price_bps`.
+    pub price_bps: u32,
+    pub remaining_amount: U128,
+}
+
+/// BuybackBookApi maintains a resting limit-order book of buyback bids matched against grants'
+/// outstanding `order_amount` (the sell side), instead of `GrantApi::buy`'s one-shot sweep at a
+/// single flat percentage. Bids rest until matched or cancelled, filling sells partially with
+/// price-time priority: highest `price_bps` first, and within a price level whichever bid was
+/// posted first (lowest `order_id`).
+pub trait BuybackBookApi {
+    /// Posts a resting bid to buy up to `amount` of outstanding `order_amount` at `price_bps`,
+    /// reserving that bid's notional cost (`amount * price_bps / 10_000`) out of `spare_balance`
+    /// up front so the book can never promise more than the contract can pay. Returns the new
+    /// bid's `order_id`.
+    fn post_buy_order(&mut self, price_bps: u32, amount: U128) -> u64;
+
+    /// Cancels a still-resting bid, refunding its unfilled notional back to `spare_balance`.
+    fn cancel_buy(&mut self, order_id: u64);
+
+    /// Returns every resting bid, ordered highest `price_bps` first and, within a price level,
+    /// lowest `order_id` (i.e. posted earliest) first — the order `match_buy_orders` fills them.
+    fn get_buyback_book(&self) -> Vec<BuyOrder>;
+
+    /// Matches the book's resting bids against `account_ids`' outstanding `order_amount`s in
+    /// price-time priority, highest bid first. A grant sells to a bid only if the bid's
+    /// `price_bps` clears its own `min_buy_bps` reserve (same rule `buy` uses). Each match
+    /// partially (or fully) fills both sides: the grant's `order_amount` and the bid's
+    /// `remaining_amount` are decremented by the same amount, a bid is removed from the book once
+    /// its `remaining_amount` reaches zero, and a grant is locked (`Grant::locked`) and paid out
+    /// through the same `pending_amount`/`ft_transfer`/`on_authorize_complete` path `authorize`
+    /// uses, rather than `buy`'s instant bookkeeping credit — a genuine transfer has to clear
+    /// before the sale is final. A grant already locked or condition-gated is skipped, same as
+    /// `buy`. Returns the subset of `account_ids` that had at least one grant matched.
+    fn match_buy_orders(&mut self, account_ids: Vec<AccountId>) -> Vec<AccountId>;
+
+    /// Posts a resting ask selling up to `amount` of the caller's `issue_at` grant's outstanding
+    /// `order_amount` at a minimum rate of `min_rate`, the sell-side counterpart to
+    /// `post_buy_order`. Reserves `amount` out of the grant's `order_amount` up front (mirroring
+    /// how `post_buy_order` reserves its notional out of `spare_balance`) so the same tokens
+    /// can't also be swept by `claim`/`buy`/`authorize` while the ask rests. Returns the new
+    /// ask's `order_id`.
+    fn place_sell_order(&mut self, issue_at: u32, amount: U128, min_rate: u32) -> u64;
+
+    /// Cancels a still-resting ask, restoring its unfilled `remaining_amount` back into the
+    /// backing grant's `order_amount`. Only the account that posted it may cancel it.
+    fn cancel_sell_order(&mut self, order_id: u64);
+
+    /// Returns every resting ask, ordered lowest `price_bps` first (cheapest offer) and, within a
+    /// price level, lowest `order_id` (i.e. posted earliest) first — the order `match_sell_orders`
+    /// fills them.
+    fn get_sell_orders(&self) -> Vec<SellOrder>;
+
+    /// Crosses the book: matches resting bids (`buy_orders`, highest price first) against
+    /// resting asks (`sell_orders`, lowest price first) wherever the bid's `price_bps` meets or
+    /// exceeds the ask's, filling each pair for `min(bid.remaining_amount, ask.remaining_amount)`
+    /// until no more pairs cross or either side of the book is empty. Like `match_buy_orders`,
+    /// each fill locks the ask's grant and pays it out through the `pending_amount`/
+    /// `ft_transfer`/`on_authorize_complete` pipeline rather than crediting `claimed_amount`
+    /// instantly. An ask whose grant is currently locked or condition-gated is left resting
+    /// rather than filled, preserving the existing invariant that a grant under pending transfer
+    /// is skipped — it will be considered again on a later call once unlocked. Returns the
+    /// distinct accounts that had at least one ask filled.
+    fn match_sell_orders(&mut self) -> Vec<AccountId>;
+}
+
+#[near]
+impl BuybackBookApi for Contract {
+    fn post_buy_order(&mut self, price_bps: u32, amount: U128) -> u64 {
+        self.require_role_active(&Role::Executor);
+        self.require_operation_unpaused(Operation::Buy);
+
+        require!(
+            price_bps > 0 && price_bps <= 10_000,
+            "price_bps must be between 1 and 10_000"
+        );
+        let amount = assert_nonzero_amount(amount.0, || "post_buy_order");
+
+        let notional = (amount * price_bps as u128) / 10_000;
+        require!(
+            self.spare_balance.0 >= notional,
+            "spare_balance cannot cover this bid's notional cost"
+        );
+        self.spare_balance.0 -= notional;
+
+        self.next_buy_order_id += 1;
+        let order_id = self.next_buy_order_id;
+        self.buy_orders.insert(
+            order_id,
+            BuyOrder {
+                order_id,
+                bidder: env::predecessor_account_id(),
+                price_bps,
+                remaining_amount: U128::from(amount),
+            },
+        );
+
+        order_id
+    }
+
+    fn cancel_buy(&mut self, order_id: u64) {
+        self.require_role_active(&Role::Executor);
+
+        let order = self
+            .buy_orders
+            .remove(&order_id)
+            .unwrap_or_else(|| near_sdk::env::panic_str("Buy order not found"));
+
+        let notional = (order.remaining_amount.0 * order.price_bps as u128) / 10_000;
+        self.spare_balance.0 += notional;
+    }
+
+    fn get_buyback_book(&self) -> Vec<BuyOrder> {
+        let mut book: Vec<BuyOrder> = self.buy_orders.values().cloned().collect();
+        book.sort_by(|a, b| {
+            b.price_bps
+                .cmp(&a.price_bps)
+                .then(a.order_id.cmp(&b.order_id))
+        });
+        book
+    }
+
+    fn match_buy_orders(&mut self, account_ids: Vec<AccountId>) -> Vec<AccountId> {
+        self.require_role_active(&Role::Executor);
+        self.require_operation_unpaused(Operation::Buy);
+
+        self.next_batch_id += 1;
+        let batch_id = self.next_batch_id;
+
+        let mut bid_ids: Vec<u64> = self.buy_orders.keys().copied().collect();
+        bid_ids.sort_by(|a, b| {
+            let price_a = self.buy_orders.get(a).unwrap().price_bps;
+            let price_b = self.buy_orders.get(b).unwrap().price_bps;
+            price_b.cmp(&price_a).then(a.cmp(b))
+        });
+
+        let mut transfers = Vec::new();
+        let mut transfer_keys = Vec::new();
+        let mut drained_bids = Vec::new();
+
+        for bid_id in bid_ids {
+            let price_bps = self.buy_orders.get(&bid_id).unwrap().price_bps;
+            let mut bid_remaining = self.buy_orders.get(&bid_id).unwrap().remaining_amount.0;
+
+            for account_id in &account_ids {
+                if bid_remaining == 0 {
+                    break;
+                }
+
+                let Some(account) = self.accounts.get_mut(account_id) else {
+                    continue;
+                };
+
+                for (issue_at, grant) in account.grants.iter_mut() {
+                    if bid_remaining == 0 {
+                        break;
+                    }
+                    if grant.locked || !grant.conditions.is_empty() {
+                        continue;
+                    }
+
+                    let order_amount = grant.order_amount.0;
+                    if order_amount == 0 {
+                        continue;
+                    }
+
+                    let min_buy_bps = grant.min_buy_bps.unwrap_or(0);
+                    if min_buy_bps > price_bps {
+                        continue;
+                    }
+
+                    let match_amount = cmp::min(order_amount, bid_remaining);
+
+                    grant.order_amount = U128::from(order_amount - match_amount);
+                    grant.locked = true;
+                    grant.pending_amount = U128::from(match_amount);
+                    grant.locked_batch_id = Some(batch_id);
+                    grant.locked_at_block_height = Some(env::block_height());
+                    grant.locked_bps = Some(price_bps);
+
+                    bid_remaining -= match_amount;
+
+                    transfers.push((account_id.clone(), match_amount));
+                    transfer_keys.push(TransferKey {
+                        account_id: account_id.clone(),
+                        issue_at: *issue_at,
+                    });
+                }
+            }
+
+            let bid = self.buy_orders.get_mut(&bid_id).unwrap();
+            bid.remaining_amount = U128::from(bid_remaining);
+            if bid_remaining == 0 {
+                drained_bids.push(bid_id);
+            }
+        }
+
+        for bid_id in drained_bids {
+            self.buy_orders.remove(&bid_id);
+        }
+
+        if transfers.is_empty() {
+            return Vec::new();
+        }
+
+        let per_transfer_gas = GasBudget::new()
+            .reserve(GAS_FOR_RESOLVE_TRANSFER)
+            .split_remaining(transfers.len() as u64, GAS_FOR_FT_TRANSFER_CALL, || {
+                "Transfer on `match_buy_orders` call."
+            });
+
+        let mut batch_promise = Promise::new(self.token_id.clone());
+        for (account_id, amount) in &transfers {
+            batch_promise = batch_promise.function_call(
+                "ft_transfer".to_string(),
+                serde_json::to_vec(&serde_json::json!({
+                    "receiver_id": account_id,
+                    "amount": amount.to_string()
+                }))
+                .unwrap(),
+                NearToken::from_yoctonear(1),
+                per_transfer_gas,
+            );
+        }
+
+        batch_promise.then(
+            Promise::new(env::current_account_id()).function_call(
+                "on_authorize_complete".to_string(),
+                serde_json::to_vec(&serde_json::json!({
+                    "transfer_keys": transfer_keys,
+                    "gas_per_transfer": per_transfer_gas.as_gas(),
+                    "fee_amount": U128::from(0)
+                }))
+                .unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_RESOLVE_TRANSFER,
+            ),
+        );
+
+        let filled_accounts: HashSet<AccountId> = transfer_keys
+            .into_iter()
+            .map(|key| key.account_id)
+            .collect();
+        account_ids
+            .into_iter()
+            .filter(|account_id| filled_accounts.contains(account_id))
+            .collect()
+    }
+
+    fn place_sell_order(&mut self, issue_at: u32, amount: U128, min_rate: u32) -> u64 {
+        let account_id = env::predecessor_account_id();
+        self.require_operation_unpaused(Operation::Buy);
+
+        require!(
+            min_rate > 0 && min_rate <= 10_000,
+            "min_rate must be between 1 and 10_000"
+        );
+        let amount = assert_nonzero_amount(amount.0, || "place_sell_order");
+
+        let grant = self
+            .accounts
+            .get_mut(&account_id)
+            .and_then(|account| account.grants.get_mut(&issue_at))
+            .unwrap_or_else(|| {
+                env::panic_str("No grant found for this account at the given issue date")
+            });
+
+        require!(
+            !grant.locked && grant.conditions.is_empty(),
+            "Grant is locked or condition-gated"
+        );
+        require!(
+            grant.order_amount.0 >= amount,
+            "Grant's order_amount cannot cover this sell order"
+        );
+
+        grant.order_amount.0 -= amount;
+
+        self.next_sell_order_id += 1;
+        let order_id = self.next_sell_order_id;
+        self.sell_orders.insert(
+            order_id,
+            SellOrder {
+                order_id,
+                account_id,
+                issue_at,
+                price_bps: min_rate,
+                remaining_amount: U128::from(amount),
+            },
+        );
+
+        order_id
+    }
+
+    fn cancel_sell_order(&mut self, order_id: u64) {
+        let order = self
+            .sell_orders
+            .get(&order_id)
+            .unwrap_or_else(|| env::panic_str("Sell order not found"))
+            .clone();
+
+        require!(
+            env::predecessor_account_id() == order.account_id,
+            "Only the account that posted this sell order may cancel it"
+        );
+
+        if let Some(grant) = self
+            .accounts
+            .get_mut(&order.account_id)
+            .and_then(|account| account.grants.get_mut(&order.issue_at))
+        {
+            grant.order_amount.0 += order.remaining_amount.0;
+        }
+
+        self.sell_orders.remove(&order_id);
+    }
+
+    fn get_sell_orders(&self) -> Vec<SellOrder> {
+        let mut book: Vec<SellOrder> = self.sell_orders.values().cloned().collect();
+        book.sort_by(|a, b| {
+            a.price_bps
+                .cmp(&b.price_bps)
+                .then(a.order_id.cmp(&b.order_id))
+        });
+        book
+    }
+
+    fn match_sell_orders(&mut self) -> Vec<AccountId> {
+        self.require_role_active(&Role::Executor);
+        self.require_operation_unpaused(Operation::Buy);
+
+        self.next_batch_id += 1;
+        let batch_id = self.next_batch_id;
+
+        let mut bid_ids: Vec<u64> = self.buy_orders.keys().copied().collect();
+        bid_ids.sort_by(|a, b| {
+            let price_a = self.buy_orders.get(a).unwrap().price_bps;
+            let price_b = self.buy_orders.get(b).unwrap().price_bps;
+            price_b.cmp(&price_a).then(a.cmp(b))
+        });
+
+        let mut ask_ids: Vec<u64> = self.sell_orders.keys().copied().collect();
+        ask_ids.sort_by(|a, b| {
+            let price_a = self.sell_orders.get(a).unwrap().price_bps;
+            let price_b = self.sell_orders.get(b).unwrap().price_bps;
+            price_a.cmp(&price_b).then(a.cmp(b))
+        });
+
+        let mut transfers = Vec::new();
+        let mut transfer_keys = Vec::new();
+        let mut drained_bids = Vec::new();
+        let mut drained_asks = Vec::new();
+
+        for bid_id in bid_ids {
+            let bid_price = self.buy_orders.get(&bid_id).unwrap().price_bps;
+            let mut bid_remaining = self.buy_orders.get(&bid_id).unwrap().remaining_amount.0;
+
+            for &ask_id in &ask_ids {
+                if bid_remaining == 0 {
+                    break;
+                }
+                if drained_asks.contains(&ask_id) {
+                    continue;
+                }
+
+                let ask = self.sell_orders.get(&ask_id).unwrap().clone();
+                if ask.price_bps > bid_price {
+                    // Asks are sorted ascending, so once one is priced above this bid, no later
+                    // ask in this pass crosses it either.
+                    break;
+                }
+
+                let Some(grant) = self
+                    .accounts
+                    .get_mut(&ask.account_id)
+                    .and_then(|account| account.grants.get_mut(&ask.issue_at))
+                else {
+                    continue;
+                };
+                if grant.locked || !grant.conditions.is_empty() {
+                    continue;
+                }
+
+                let match_amount = cmp::min(bid_remaining, ask.remaining_amount.0);
+                if match_amount == 0 {
+                    continue;
+                }
+
+                grant.locked = true;
+                grant.pending_amount = U128::from(match_amount);
+                grant.locked_batch_id = Some(batch_id);
+                grant.locked_at_block_height = Some(env::block_height());
+                grant.locked_bps = Some(ask.price_bps);
+
+                bid_remaining -= match_amount;
+
+                let ask_mut = self.sell_orders.get_mut(&ask_id).unwrap();
+                ask_mut.remaining_amount = U128::from(ask.remaining_amount.0 - match_amount);
+                if ask_mut.remaining_amount.0 == 0 {
+                    drained_asks.push(ask_id);
+                }
+
+                transfers.push((ask.account_id.clone(), match_amount));
+                transfer_keys.push(TransferKey {
+                    account_id: ask.account_id,
+                    issue_at: ask.issue_at,
+                });
+            }
+
+            let bid = self.buy_orders.get_mut(&bid_id).unwrap();
+            bid.remaining_amount = U128::from(bid_remaining);
+            if bid_remaining == 0 {
+                drained_bids.push(bid_id);
+            }
+        }
+
+        for bid_id in drained_bids {
+            self.buy_orders.remove(&bid_id);
+        }
+        for ask_id in drained_asks {
+            self.sell_orders.remove(&ask_id);
+        }
+
+        if transfers.is_empty() {
+            return Vec::new();
+        }
+
+        let per_transfer_gas = GasBudget::new()
+            .reserve(GAS_FOR_RESOLVE_TRANSFER)
+            .split_remaining(transfers.len() as u64, GAS_FOR_FT_TRANSFER_CALL, || {
+                "Transfer on `match_sell_orders` call."
+            });
+
+        let mut batch_promise = Promise::new(self.token_id.clone());
+        for (account_id, amount) in &transfers {
+            batch_promise = batch_promise.function_call(
+                "ft_transfer".to_string(),
+                serde_json::to_vec(&serde_json::json!({
+                    "receiver_id": account_id,
+                    "amount": amount.to_string()
+                }))
+                .unwrap(),
+                NearToken::from_yoctonear(1),
+                per_transfer_gas,
+            );
+        }
+
+        batch_promise.then(
+            Promise::new(env::current_account_id()).function_call(
+                "on_authorize_complete".to_string(),
+                serde_json::to_vec(&serde_json::json!({
+                    "transfer_keys": transfer_keys,
+                    "gas_per_transfer": per_transfer_gas.as_gas(),
+                    "fee_amount": U128::from(0)
+                }))
+                .unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_RESOLVE_TRANSFER,
+            ),
+        );
+
+        let filled_accounts: HashSet<AccountId> = transfer_keys
+            .into_iter()
+            .map(|key| key.account_id)
+            .collect();
+        filled_accounts.into_iter().collect()
+    }
+}
+
+impl Contract {
+    /// Removes every resting `SellOrder` posted against `account_id`'s `issue_at` grant,
+    /// returning the summed `remaining_amount` reclaimed. Called by `GrantApi::terminate` and
+    /// `GrantApi::terminate_vesting` before clawing back a grant, so a resting ask can't keep
+    /// offering tokens a terminated grant no longer has outstanding — the reclaimed amount is
+    /// merged back into `order_amount` so the grant's own clawback math (which caps
+    /// `order_amount` at what's actually still vested) sees and correctly forfeits any of it the
+    /// termination leaves unvested.
+    pub(crate) fn release_sell_orders_for_grant(
+        &mut self,
+        account_id: &AccountId,
+        issue_at: u32,
+    ) -> u128 {
+        let stale_ids: Vec<u64> = self
+            .sell_orders
+            .iter()
+            .filter(|(_, order)| &order.account_id == account_id && order.issue_at == issue_at)
+            .map(|(order_id, _)| *order_id)
+            .collect();
+
+        let mut reclaimed = 0u128;
+        for order_id in stale_ids {
+            reclaimed += self
+                .sell_orders
+                .remove(&order_id)
+                .unwrap()
+                .remaining_amount
+                .0;
+        }
+
+        reclaimed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{json_types::U128, test_utils::accounts, AccountId};
+    use rstest::*;
+
+    use crate::{book::BuybackBookApi, tests::context::TestContext, tests::fixtures::*, Contract};
+
+    #[rstest]
+    fn post_buy_order_reserves_notional_from_spare_balance(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_executor();
+        let order_id = contract.post_buy_order(5_000, 2_000.into());
+
+        assert_eq!(order_id, 1);
+        assert_eq!(contract.spare_balance.0, 9_000);
+
+        let book = contract.get_buyback_book();
+        assert_eq!(book.len(), 1);
+        assert_eq!(book[0].remaining_amount.0, 2_000);
+    }
+
+    #[rstest]
+    fn cancel_buy_refunds_unfilled_notional(mut context: TestContext, mut contract: Contract) {
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_executor();
+        let order_id = contract.post_buy_order(5_000, 2_000.into());
+        assert_eq!(contract.spare_balance.0, 9_000);
+
+        contract.cancel_buy(order_id);
+
+        assert_eq!(contract.spare_balance.0, 10_000);
+        assert!(contract.get_buyback_book().is_empty());
+    }
+
+    #[rstest]
+    fn get_buyback_book_orders_by_price_then_order_id(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_executor();
+        let low = contract.post_buy_order(4_000, 1_000.into());
+        let high = contract.post_buy_order(8_000, 1_000.into());
+        let mid = contract.post_buy_order(6_000, 1_000.into());
+
+        let book = contract.get_buyback_book();
+        let ids: Vec<u64> = book.iter().map(|order| order.order_id).collect();
+        assert_eq!(ids, vec![high, mid, low]);
+    }
+
+    #[rstest]
+    fn match_buy_orders_fills_highest_bid_first_and_locks_the_grant(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_executor();
+        let low_bid = contract.post_buy_order(4_000, 1_000.into());
+        contract.post_buy_order(8_000, 2_000.into());
+
+        let filled = contract.match_buy_orders(vec![alice.clone()]);
+        assert_eq!(filled, vec![alice.clone()]);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert!(grant.locked);
+        assert_eq!(grant.pending_amount.0, 2_000);
+        assert_eq!(grant.order_amount.0, 1_000);
+
+        // The higher bid fully fills and drops out of the book; the grant is locked after that
+        // fill, so the lower bid is left resting untouched behind it.
+        let book = contract.get_buyback_book();
+        assert_eq!(book.len(), 1);
+        assert_eq!(book[0].order_id, low_bid);
+        assert_eq!(book[0].remaining_amount.0, 1_000);
+    }
+
+    #[rstest]
+    fn match_buy_orders_skips_grants_below_reserve_price(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+            grant.min_buy_bps = Some(9_000);
+        }
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_executor();
+        contract.post_buy_order(8_000, 2_000.into());
+
+        let filled = contract.match_buy_orders(vec![alice.clone()]);
+        assert!(filled.is_empty());
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert!(!grant.locked);
+        assert_eq!(grant.order_amount.0, 3_000);
+    }
+
+    #[rstest]
+    fn post_buy_order_requires_executor_role(mut context: TestContext, mut contract: Contract) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        contract.spare_balance = 10_000.into();
+
+        context.switch_account(&accounts(9));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.post_buy_order(5_000, 1_000.into());
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn place_sell_order_reserves_amount_from_order_amount(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+
+        context.switch_account(&alice);
+        let order_id = contract.place_sell_order(1_000, 1_200.into(), 6_000);
+        assert_eq!(order_id, 1);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 1_800);
+
+        let asks = contract.get_sell_orders();
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks[0].remaining_amount.0, 1_200);
+    }
+
+    #[rstest]
+    fn cancel_sell_order_refunds_into_order_amount(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+
+        context.switch_account(&alice);
+        let order_id = contract.place_sell_order(1_000, 1_200.into(), 6_000);
+        contract.cancel_sell_order(order_id);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 3_000);
+        assert!(contract.get_sell_orders().is_empty());
+    }
+
+    #[rstest]
+    fn cancel_sell_order_requires_the_posting_account(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+
+        context.switch_account(&alice);
+        let order_id = contract.place_sell_order(1_000, 1_200.into(), 6_000);
+
+        context.switch_account(&bob);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.cancel_sell_order(order_id);
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn get_sell_orders_orders_by_price_then_order_id(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+
+        context.switch_account(&alice);
+        let high = contract.place_sell_order(1_000, 1_000.into(), 8_000);
+        let low = contract.place_sell_order(1_000, 1_000.into(), 4_000);
+        let mid = contract.place_sell_order(1_000, 1_000.into(), 6_000);
+
+        let asks = contract.get_sell_orders();
+        let ids: Vec<u64> = asks.iter().map(|order| order.order_id).collect();
+        assert_eq!(ids, vec![low, mid, high]);
+    }
+
+    #[rstest]
+    fn match_sell_orders_crosses_the_best_bid_and_ask_and_locks_the_grant(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+        contract.spare_balance = 10_000.into();
+
+        context.switch_account(&alice);
+        contract.place_sell_order(1_000, 2_000.into(), 5_000);
+
+        context.switch_to_executor();
+        contract.post_buy_order(6_000, 1_500.into());
+
+        let filled = contract.match_sell_orders();
+        assert_eq!(filled, vec![alice.clone()]);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert!(grant.locked);
+        assert_eq!(grant.pending_amount.0, 1_500);
+        // The ask reserved 2_000 up front at placement; the bid only crossed for 1_500, leaving
+        // the ask resting with its remainder.
+        assert_eq!(grant.order_amount.0, 1_000);
+
+        let asks = contract.get_sell_orders();
+        assert_eq!(asks.len(), 1);
+        assert_eq!(asks[0].remaining_amount.0, 500);
+    }
+
+    #[rstest]
+    fn match_sell_orders_leaves_a_locked_grants_ask_resting(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+        contract.spare_balance = 10_000.into();
+
+        context.switch_account(&alice);
+        contract.place_sell_order(1_000, 2_000.into(), 5_000);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.locked = true;
+        }
+
+        context.switch_to_executor();
+        contract.post_buy_order(6_000, 1_500.into());
+
+        let filled = contract.match_sell_orders();
+        assert!(filled.is_empty());
+        assert_eq!(contract.get_sell_orders().len(), 1);
+    }
+
+    #[rstest]
+    fn terminate_reclaims_a_resting_sell_order_instead_of_leaving_it_tradeable(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        use crate::grant::GrantApi;
+
+        contract.create_grant_internal(&alice, 0, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&0)
+                .unwrap();
+            grant.order_amount = U128::from(3_000);
+        }
+
+        context.switch_account(&alice);
+        contract.place_sell_order(0, 2_000.into(), 5_000);
+
+        // Terminating at the grant's issue date means nothing has vested yet, so the clawback
+        // should claim back the full order_amount, including the 2_000 a resting sell order had
+        // carved out of it.
+        context.switch_to_executor();
+        contract.terminate(alice.clone(), 0);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&0)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 0);
+        assert!(contract.get_sell_orders().is_empty());
+    }
+}
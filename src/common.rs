@@ -1,25 +1,61 @@
 use std::fmt::Display;
 
-use near_sdk::env::{self, panic_str};
+use near_sdk::{
+    env::{self, panic_str},
+    Gas,
+};
 
 pub const ONE_DAY_IN_SECONDS: u32 = 86_400;
 pub const ONE_YEAR_IN_SECONDS: u32 = 31_556_952;
 
-const TOKEN_UNITS: u128 = 1_000_000_000_000_000_000; // 1 token = 1e18 token units
+/// Decimals SWEAT (and every other token this contract has historically paid out) happens to
+/// use. Kept only as the default `ToOtto` falls back to; a token configured with different
+/// NEP-148 `decimals` must go through `ToTokenUnits::to_token_units` instead.
+const DEFAULT_DECIMALS: u8 = 18;
 
-pub trait ToOtto {
-    fn to_otto(self) -> u128;
+/// Converts a human-readable token amount into raw on-chain token units for a token with the
+/// given number of `decimals`, per its NEP-148 `ft_metadata`. Panics on overflow instead of
+/// silently wrapping, since a wrapped amount would pay out (or lock up) the wrong balance.
+pub trait ToTokenUnits {
+    /// Panics if the conversion overflows `u128`.
+    fn to_token_units(self, decimals: u8) -> u128;
+
+    /// Returns `None` instead of panicking if the conversion overflows `u128`.
+    fn checked_to_token_units(self, decimals: u8) -> Option<u128>;
 }
 
-impl ToOtto for u128 {
-    fn to_otto(self) -> u128 {
-        self * TOKEN_UNITS
+impl ToTokenUnits for u128 {
+    fn to_token_units(self, decimals: u8) -> u128 {
+        self.checked_to_token_units(decimals)
+            .unwrap_or_else(|| panic_str("Token amount overflowed u128 during decimals conversion"))
+    }
+
+    fn checked_to_token_units(self, decimals: u8) -> Option<u128> {
+        10u128
+            .checked_pow(u32::from(decimals))
+            .and_then(|unit| self.checked_mul(unit))
+    }
+}
+
+impl ToTokenUnits for u64 {
+    fn to_token_units(self, decimals: u8) -> u128 {
+        u128::from(self).to_token_units(decimals)
+    }
+
+    fn checked_to_token_units(self, decimals: u8) -> Option<u128> {
+        u128::from(self).checked_to_token_units(decimals)
     }
 }
 
-impl ToOtto for u64 {
+/// Thin wrapper over `ToTokenUnits` defaulting to `DEFAULT_DECIMALS`, kept for call sites
+/// (and tests) written before tokens with non-SWEAT decimals were supported.
+pub trait ToOtto {
+    fn to_otto(self) -> u128;
+}
+
+impl<T: ToTokenUnits> ToOtto for T {
     fn to_otto(self) -> u128 {
-        u128::from(self) * TOKEN_UNITS
+        self.to_token_units(DEFAULT_DECIMALS)
     }
 }
 
@@ -28,6 +64,22 @@ pub(crate) fn now() -> u32 {
         .unwrap_or_else(|_| panic_str("Failed to convert current timestamp to seconds"))
 }
 
+/// Rejects a caller-supplied amount of zero instead of letting it flow through to a transfer
+/// promise (or a grant that can never pay out anything), per the convention that a value-moving
+/// entry point should revert on a zero amount rather than silently create a no-op. Returns
+/// `amount` unchanged so it composes at the call site, e.g.
+/// `assert_nonzero_amount(amount.to_otto(), || "...")`.
+pub(crate) fn assert_nonzero_amount<Message: Display>(
+    amount: u128,
+    error: impl FnOnce() -> Message,
+) -> u128 {
+    if amount == 0 {
+        panic_str(&format!("Amount must be non-zero: {}", error()));
+    }
+
+    amount
+}
+
 pub(crate) fn assert_gas<Message: Display>(gas_needed: u64, error: impl FnOnce() -> Message) {
     let gas_left = env::prepaid_gas().as_gas() - env::used_gas().as_gas();
 
@@ -42,3 +94,205 @@ pub(crate) fn assert_gas<Message: Display>(gas_needed: u64, error: impl FnOnce()
         ));
     }
 }
+
+/// Reserves the fixed-cost legs a method is about to schedule (e.g. a single resolve callback),
+/// then splits whatever prepaid gas is left evenly across its dynamic calls (e.g. one
+/// `ft_transfer` per account in a batch), in place of a hand-tuned per-call literal that either
+/// over-reserves for a small batch or starves a large one.
+pub(crate) struct GasBudget {
+    reserved: Gas,
+}
+
+impl GasBudget {
+    pub(crate) fn new() -> Self {
+        Self {
+            reserved: Gas::from_gas(0),
+        }
+    }
+
+    /// Sets aside `gas` for a leg that isn't split across the dynamic calls, such as the resolve
+    /// callback a batch of transfers is `.then()`-chained to.
+    pub(crate) fn reserve(mut self, gas: Gas) -> Self {
+        self.reserved = self.reserved.saturating_add(gas);
+        self
+    }
+
+    /// Asserts (once, via `assert_gas`) that enough prepaid gas remains to give each of
+    /// `dynamic_calls` at least `per_call_floor` on top of every `reserve`d leg, then returns the
+    /// evenly split amount each dynamic call should actually attach.
+    pub(crate) fn split_remaining<Message: Display>(
+        self,
+        dynamic_calls: u64,
+        per_call_floor: Gas,
+        error: impl FnOnce() -> Message,
+    ) -> Gas {
+        assert_gas(
+            self.reserved.as_gas() + per_call_floor.as_gas() * dynamic_calls,
+            error,
+        );
+
+        let remaining = env::prepaid_gas()
+            .as_gas()
+            .saturating_sub(env::used_gas().as_gas())
+            .saturating_sub(self.reserved.as_gas());
+
+        Gas::from_gas(remaining / dynamic_calls)
+    }
+}
+
+/// Pessimistically estimates whether a batch operation over many items (e.g. one account per
+/// iteration) can run to completion within the gas actually left, modeled on NEAR's own
+/// pessimistic-inflation safety margin: inflate the expected per-item cost by `safety_ratio_bps`
+/// rather than trust the average case, since running out of gas mid-loop leaves earlier items
+/// mutated and later ones silently skipped.
+pub(crate) struct PessimisticGasEstimator {
+    per_item: Gas,
+    safety_ratio_bps: u32,
+}
+
+impl PessimisticGasEstimator {
+    /// Inflates `per_item` by 3%, a margin comparable to NEAR's own pessimistic gas inflation
+    /// for cross-contract calls.
+    const DEFAULT_SAFETY_RATIO_BPS: u32 = 10_300;
+
+    pub(crate) fn new(per_item: Gas) -> Self {
+        Self {
+            per_item,
+            safety_ratio_bps: Self::DEFAULT_SAFETY_RATIO_BPS,
+        }
+    }
+
+    fn pessimistic_cost(&self, items: u64) -> u64 {
+        let cost = u128::from(self.per_item.as_gas())
+            * u128::from(items)
+            * u128::from(self.safety_ratio_bps)
+            / 10_000;
+
+        u64::try_from(cost).unwrap_or(u64::MAX)
+    }
+
+    /// Panics (via `assert_gas`) unless enough prepaid gas remains to pessimistically cover all
+    /// `total_items`. Intended as an entry-time refusal for callers (e.g. an atomic batch) that
+    /// can't sensibly resume a partially processed tail.
+    pub(crate) fn require_affordable<Message: Display>(
+        &self,
+        total_items: u64,
+        error: impl FnOnce() -> Message,
+    ) {
+        assert_gas(self.pessimistic_cost(total_items), error);
+    }
+
+    /// Returns `true` if enough prepaid gas remains to pessimistically cover `remaining_items`
+    /// more items, without panicking. Intended for a mid-loop check so a batch can stop early and
+    /// hand back a continuation cursor instead of running out of gas destructively.
+    pub(crate) fn can_afford(&self, remaining_items: u64) -> bool {
+        let gas_left = env::prepaid_gas()
+            .as_gas()
+            .saturating_sub(env::used_gas().as_gas());
+
+        gas_left >= self.pessimistic_cost(remaining_items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::Gas;
+    use rstest::rstest;
+
+    use super::{assert_nonzero_amount, GasBudget, PessimisticGasEstimator, ToOtto, ToTokenUnits};
+    use crate::tests::{context::TestContext, fixtures::*};
+
+    #[test]
+    fn assert_nonzero_amount_passes_through_nonzero_amounts() {
+        assert_eq!(assert_nonzero_amount(5, || "test"), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be non-zero")]
+    fn assert_nonzero_amount_panics_on_zero() {
+        assert_nonzero_amount(0, || "test");
+    }
+
+    #[test]
+    fn to_token_units_scales_by_decimals() {
+        assert_eq!(1u128.to_token_units(6), 1_000_000);
+        assert_eq!(1u128.to_token_units(24), 1_000_000_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn to_otto_defaults_to_eighteen_decimals() {
+        assert_eq!(1u128.to_otto(), 1u128.to_token_units(18));
+    }
+
+    #[test]
+    fn checked_to_token_units_returns_none_on_overflow() {
+        assert_eq!(u128::MAX.checked_to_token_units(18), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflowed")]
+    fn to_token_units_panics_on_overflow() {
+        u128::MAX.to_token_units(18);
+    }
+
+    #[rstest]
+    fn gas_budget_splits_remaining_evenly_across_dynamic_calls(mut context: TestContext) {
+        context.with_gas_attached(Gas::from_tgas(100), || {
+            let per_call = GasBudget::new()
+                .reserve(Gas::from_tgas(10))
+                .split_remaining(3, Gas::from_tgas(5), || "test");
+
+            assert_eq!(per_call, Gas::from_tgas(30));
+        });
+    }
+
+    #[rstest]
+    fn gas_budget_panics_when_not_enough_gas_for_the_floor(mut context: TestContext) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.with_gas_attached(Gas::from_tgas(20), || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                GasBudget::new()
+                    .reserve(Gas::from_tgas(10))
+                    .split_remaining(3, Gas::from_tgas(5), || "test");
+            }));
+
+            assert!(result.is_err());
+        });
+    }
+
+    #[rstest]
+    fn pessimistic_gas_estimator_affords_what_it_pre_checked(mut context: TestContext) {
+        context.with_gas_attached(Gas::from_tgas(100), || {
+            let estimator = PessimisticGasEstimator::new(Gas::from_tgas(1));
+
+            estimator.require_affordable(10, || "test");
+            assert!(estimator.can_afford(10));
+        });
+    }
+
+    #[rstest]
+    fn pessimistic_gas_estimator_refuses_to_start_when_unaffordable(mut context: TestContext) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.with_gas_attached(Gas::from_tgas(5), || {
+            let estimator = PessimisticGasEstimator::new(Gas::from_tgas(1));
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                estimator.require_affordable(10, || "test");
+            }));
+
+            assert!(result.is_err());
+        });
+    }
+
+    #[rstest]
+    fn pessimistic_gas_estimator_can_afford_reflects_safety_ratio(mut context: TestContext) {
+        context.with_gas_attached(Gas::from_tgas(103), || {
+            let estimator = PessimisticGasEstimator::new(Gas::from_tgas(1));
+
+            assert!(estimator.can_afford(100));
+            assert!(!estimator.can_afford(101));
+        });
+    }
+}
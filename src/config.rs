@@ -1,9 +1,23 @@
-use near_sdk::near;
+use near_sdk::{json_types::U128, near};
 
-use crate::{Config, Contract, ContractExt};
+use crate::{common::ToTokenUnits, Config, Contract, ContractExt, FeeSchedule};
 
 pub trait ConfigApi {
     fn get_config(&self) -> Config;
+
+    /// Sets (or, with `None`, clears) the commission schedule `authorize`/`buy` apply.
+    /// Owner-gated, same as `grant_role`/`pause_operation`.
+    fn set_fee_schedule(&mut self, fee_schedule: Option<FeeSchedule>);
+
+    /// Returns the currently configured fee schedule, if any.
+    fn get_fee_schedule(&self) -> Option<FeeSchedule>;
+
+    /// Converts a whole-token amount into this contract's raw on-chain token units using
+    /// `Config::token_decimals`, the decimals `token_id` actually advertises via its NEP-148
+    /// `ft_metadata` rather than assuming SWEAT's 18. Lets an off-chain caller compute the exact
+    /// `U128` `issue`/`buy`/`authorize`/`FtMessage` expect from a human-readable amount without
+    /// hard-coding `token_id`'s decimals on their end. Panics on `u128` overflow.
+    fn to_token_units(&self, whole_tokens: U128) -> U128;
 }
 
 #[near]
@@ -11,4 +25,85 @@ impl ConfigApi for Contract {
     fn get_config(&self) -> Config {
         self.config.clone()
     }
+
+    fn set_fee_schedule(&mut self, fee_schedule: Option<FeeSchedule>) {
+        Self::require_owner();
+
+        self.fee_schedule = fee_schedule;
+    }
+
+    fn get_fee_schedule(&self) -> Option<FeeSchedule> {
+        self.fee_schedule.clone()
+    }
+
+    fn to_token_units(&self, whole_tokens: U128) -> U128 {
+        whole_tokens
+            .0
+            .to_token_units(self.config.token_decimals)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use near_sdk::AccountId;
+    use rstest::*;
+
+    use crate::{
+        config::ConfigApi, tests::context::TestContext, tests::fixtures::*, Contract, FeeSchedule,
+    };
+
+    #[rstest]
+    fn set_fee_schedule_requires_owner(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.set_fee_schedule(Some(FeeSchedule {
+                buyback_bps: 100,
+                authorize_bps: 100,
+                fee_collector: alice.clone(),
+            }));
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.get_fee_schedule().is_none());
+    }
+
+    #[rstest]
+    fn owner_can_set_and_clear_fee_schedule(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        bob: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.set_fee_schedule(Some(FeeSchedule {
+            buyback_bps: 250,
+            authorize_bps: 50,
+            fee_collector: bob.clone(),
+        }));
+
+        let fee_schedule = contract.get_fee_schedule().unwrap();
+        assert_eq!(fee_schedule.buyback_bps, 250);
+        assert_eq!(fee_schedule.authorize_bps, 50);
+        assert_eq!(fee_schedule.fee_collector, bob);
+
+        contract.set_fee_schedule(None);
+        assert!(contract.get_fee_schedule().is_none());
+    }
+
+    #[rstest]
+    fn to_token_units_scales_by_the_configured_decimals(contract: Contract) {
+        use near_sdk::json_types::U128;
+
+        assert_eq!(
+            contract.to_token_units(U128::from(1)),
+            U128::from(1_000_000_000_000_000_000)
+        );
+    }
 }
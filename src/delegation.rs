@@ -0,0 +1,209 @@
+use near_sdk::{env, near, require, Allowance, NearToken, Promise, PublicKey};
+
+use crate::{Contract, ContractExt};
+
+/// DelegationApi lets a grant holder delegate automatic `claim` calls to a bot or custodian
+/// without exposing full account control, borrowing Orderly's restricted-access-key design: the
+/// holder registers a function-call access key, scoped to only `claim`, on the *contract's own*
+/// account rather than their own. A transaction signed with that key therefore arrives with
+/// `env::predecessor_account_id() == env::current_account_id()`; `Contract::claim_keys` maps the
+/// signing public key back to the grantee it was registered for so `claim` can still resolve the
+/// right account. Because the access key's `method_names` restriction is enforced by the NEAR
+/// runtime itself, a delegated key can never be used to call `buy`, `authorize`, `terminate`, or
+/// `issue` no matter what this module does.
+pub trait DelegationApi {
+    /// Registers `public_key` as a delegated claim key for the caller and attaches a
+    /// `claim`-only function-call access key (metered by `allowance`, or unlimited if `None`) to
+    /// the contract's own account. Panics if `public_key` is already registered to a different
+    /// account — like `remove_claim_key`, only the account a key was registered for may touch
+    /// it, so one account can never retarget another's already-deployed key to itself.
+    /// Re-registering a key already owned by the caller is a harmless no-op refresh.
+    fn register_claim_key(&mut self, public_key: PublicKey, allowance: Option<NearToken>);
+
+    /// Revokes a previously registered claim key: deletes the access key from the contract's
+    /// account and drops the `claim_keys` mapping. Only the account it was registered for may
+    /// remove it.
+    fn remove_claim_key(&mut self, public_key: PublicKey);
+}
+
+#[near]
+impl DelegationApi for Contract {
+    fn register_claim_key(&mut self, public_key: PublicKey, allowance: Option<NearToken>) {
+        let caller = env::predecessor_account_id();
+
+        if let Some(existing_owner) = self.claim_keys.get(&public_key) {
+            require!(
+                existing_owner == &caller,
+                "This claim key is already registered to a different account"
+            );
+        }
+
+        self.claim_keys.insert(public_key.clone(), caller);
+
+        let allowance = allowance
+            .map(|amount| {
+                Allowance::limited(amount)
+                    .unwrap_or_else(|| env::panic_str("allowance must be non-zero"))
+            })
+            .unwrap_or(Allowance::Unlimited);
+
+        Promise::new(env::current_account_id()).add_access_key_allowance(
+            public_key,
+            allowance,
+            env::current_account_id(),
+            "claim".to_string(),
+        );
+    }
+
+    fn remove_claim_key(&mut self, public_key: PublicKey) {
+        let caller = env::predecessor_account_id();
+        let owner = self
+            .claim_keys
+            .get(&public_key)
+            .unwrap_or_else(|| env::panic_str("No such claim key registered"));
+        require!(
+            owner == &caller,
+            "Only the account a claim key was registered for may remove it"
+        );
+
+        self.claim_keys.remove(&public_key);
+
+        Promise::new(env::current_account_id()).delete_key(public_key);
+    }
+}
+
+impl Contract {
+    /// Resolves the effective grantee for a `claim` call: `env::predecessor_account_id()` for a
+    /// normal direct call, or the `claim_keys`-registered account when invoked through a
+    /// delegated claim key, identified by `predecessor_account_id() == current_account_id()`
+    /// since that key lives on the contract's own account rather than the caller's.
+    pub(crate) fn resolve_claim_caller(&self) -> near_sdk::AccountId {
+        let predecessor = env::predecessor_account_id();
+
+        if predecessor == env::current_account_id() {
+            let signer_pk = env::signer_account_pk();
+            self.claim_keys
+                .get(&signer_pk)
+                .cloned()
+                .unwrap_or_else(|| env::panic_str("No claim key registered for the signing key"))
+        } else {
+            predecessor
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{json_types::U128, AccountId, PublicKey};
+    use rstest::*;
+
+    use crate::{
+        delegation::DelegationApi, grant::GrantApi, testing_api::DEFAULT_CLIFF,
+        tests::context::TestContext, tests::fixtures::*, Contract,
+    };
+
+    fn claim_key() -> PublicKey {
+        "ed25519:7PGseFbWxvYVgZ89K1uTJKYoKetWs7BJtbyXTRdihjTu"
+            .parse()
+            .unwrap()
+    }
+
+    #[rstest]
+    fn register_claim_key_records_the_owning_account(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        context.switch_account(&alice);
+        contract.register_claim_key(claim_key(), None);
+
+        assert_eq!(contract.claim_keys.get(&claim_key()), Some(&alice));
+    }
+
+    #[rstest]
+    fn remove_claim_key_clears_the_mapping(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        context.switch_account(&alice);
+        contract.register_claim_key(claim_key(), None);
+        contract.remove_claim_key(claim_key());
+
+        assert!(contract.claim_keys.get(&claim_key()).is_none());
+    }
+
+    #[rstest]
+    fn remove_claim_key_requires_the_registering_account(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        context.switch_account(&alice);
+        contract.register_claim_key(claim_key(), None);
+
+        context.switch_account(&bob);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.remove_claim_key(claim_key());
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(contract.claim_keys.get(&claim_key()), Some(&alice));
+    }
+
+    #[rstest]
+    fn register_claim_key_refuses_to_retarget_another_accounts_key(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        context.switch_account(&alice);
+        contract.register_claim_key(claim_key(), None);
+
+        context.switch_account(&bob);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.register_claim_key(claim_key(), None);
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(contract.claim_keys.get(&claim_key()), Some(&alice));
+    }
+
+    #[rstest]
+    fn claim_via_delegated_key_resolves_the_registered_grantee(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        context.switch_account(&alice);
+        contract.register_claim_key(claim_key(), None);
+
+        context.switch_to_claim_key(claim_key());
+        context.set_block_timestamp_in_seconds(4_000);
+        contract.claim();
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 1_000);
+    }
+
+    #[rstest]
+    fn claim_via_unregistered_key_panics(mut context: TestContext, mut contract: Contract) {
+        context.switch_to_claim_key(claim_key());
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.claim();
+        }));
+
+        assert!(result.is_err());
+    }
+}
@@ -1,16 +1,206 @@
-use near_sdk::{json_types::U128, near, AccountId};
+use near_sdk::{
+    env,
+    json_types::{Base64VecU8, U128},
+    near, AccountId,
+};
 use near_sdk_contract_tools::Nep297;
 
-#[derive(Nep297)]
-#[near(serializers = [json])]
+use crate::{Contract, ContractExt, Role};
+
+#[derive(Nep297, Clone)]
+#[near(serializers = [borsh, json])]
 #[nep297(standard = "nep171", version = "0.1.0", rename_all = "snake_case")]
 pub enum LtipEvent {
     OrderUpdate(Vec<OrderUpdateData>),
     Terminate((AccountId, Vec<(u32, u128)>)),
+    Issue((u32, Vec<(AccountId, u128)>)),
+    TopUp((AccountId, u128)),
+    Migrate((AccountId, u64, u128)),
+    TrancheConfirmed((AccountId, u32, u32)),
+    /// Emitted from the `ft_transfer` resolve callback when one or more authorized transfers
+    /// failed and their `(account_id, issue_at, amount)` were rolled back into `order_amount`.
+    TransferReverted(Vec<(AccountId, u32, u128)>),
+    /// `(account_id, issue_at, amount)` for each order `buy` filled at the offered percentage.
+    BuybackFilled(Vec<(AccountId, u32, u128)>),
+    /// `(account_id, issue_at, min_buy_bps)` for each order `buy` skipped because its reserve
+    /// price was above the offered percentage.
+    BuybackSkipped(Vec<(AccountId, u32, u32)>),
+    /// Emitted from `on_stake_idle_complete`/`on_unstake_complete` for each grant whose
+    /// `deposit_and_stake`/`withdraw` call against the staking pool failed.
+    StakeReconciled(Vec<(AccountId, u32, u128)>),
+    /// Emitted from `on_stake_spare_complete`/`on_unstake_spare_complete` when the contract's
+    /// `deposit_and_stake`/`withdraw` call against `spare_staking_pool_id` failed. The `bool` is
+    /// `true` for a failed `stake_spare`, `false` for a failed `unstake_spare`.
+    SpareStakeReconciled((bool, u128)),
+    /// Emitted from `on_authorize_complete` when an `authorize` batch's trailing fee-collector
+    /// `ft_transfer` leg failed and its amount was restored to `accrued_fees`.
+    FeeTransferReverted(u128),
+    /// Emitted from `AuthApi::grant_role` when the owner assigns a `Role` to an account.
+    RoleGranted((AccountId, Role)),
+    /// Emitted from `AuthApi::revoke_role` when the owner removes a `Role` from an account.
+    RoleRevoked((AccountId, Role)),
+    /// Emitted from `AuthApi::force_unpause` with the account (the owner) that called it.
+    ForceUnpaused(AccountId),
+    /// Emitted from `AuthApi::grant_role_in_scope` with the `(account_id, role, scope_id)` it
+    /// assigned.
+    RoleGrantedInScope((AccountId, Role, String)),
+    /// Emitted from `AuthApi::revoke_role_in_scope` with the `(account_id, role, scope_id)` it
+    /// removed.
+    RoleRevokedInScope((AccountId, Role, String)),
 }
 
-#[near(serializers = [json])]
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
 pub struct OrderUpdateData {
     pub issue_at: u32,
     pub amount: U128,
 }
+
+/// AuditApi exposes the tamper-evident hash chain that backs the event log.
+pub trait AuditApi {
+    /// Returns the current sequence index and head of the audit hash chain.
+    fn get_audit_head(&self) -> (u64, Base64VecU8);
+
+    /// Replays `events` on top of `start_head`, re-deriving the chain using `block_heights`
+    /// (one per event, in order), and returns whether the result matches the contract's
+    /// current audit head and sequence.
+    fn verify_audit(
+        &self,
+        events: Vec<LtipEvent>,
+        block_heights: Vec<u64>,
+        start_head: Base64VecU8,
+        start_sequence: u64,
+    ) -> bool;
+}
+
+#[near]
+impl AuditApi for Contract {
+    fn get_audit_head(&self) -> (u64, Base64VecU8) {
+        (
+            self.audit_sequence,
+            Base64VecU8::from(self.audit_head.to_vec()),
+        )
+    }
+
+    fn verify_audit(
+        &self,
+        events: Vec<LtipEvent>,
+        block_heights: Vec<u64>,
+        start_head: Base64VecU8,
+        start_sequence: u64,
+    ) -> bool {
+        if events.len() != block_heights.len() {
+            return false;
+        }
+
+        let mut head: [u8; 32] = match start_head.0.try_into() {
+            Ok(head) => head,
+            Err(_) => return false,
+        };
+        let mut sequence = start_sequence;
+
+        for (event, block_height) in events.iter().zip(block_heights) {
+            head = hash_chain_link(&head, event, block_height, sequence);
+            sequence += 1;
+        }
+
+        head == self.audit_head && sequence == self.audit_sequence
+    }
+}
+
+fn hash_chain_link(
+    prev_head: &[u8; 32],
+    ev: &LtipEvent,
+    block_height: u64,
+    sequence: u64,
+) -> [u8; 32] {
+    let mut buffer = prev_head.to_vec();
+    buffer.extend(
+        near_sdk::borsh::to_vec(ev).unwrap_or_else(|_| env::panic_str("Failed to serialize event")),
+    );
+    buffer.extend(block_height.to_le_bytes());
+    buffer.extend(sequence.to_le_bytes());
+
+    env::sha256(&buffer)
+        .try_into()
+        .unwrap_or_else(|_| env::panic_str("sha256 did not return 32 bytes"))
+}
+
+impl Contract {
+    /// Folds `ev` into the append-only audit hash chain and emits it as an NEP-297 event.
+    pub(crate) fn commit_event(&mut self, ev: &LtipEvent) {
+        self.audit_head = hash_chain_link(
+            &self.audit_head,
+            ev,
+            env::block_height(),
+            self.audit_sequence,
+        );
+        self.audit_sequence += 1;
+
+        ev.clone().emit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::json_types::Base64VecU8;
+    use rstest::*;
+
+    use crate::{
+        event::{AuditApi, LtipEvent},
+        tests::context::TestContext,
+        tests::fixtures::*,
+        Contract,
+    };
+
+    #[rstest]
+    fn commit_event_advances_head_and_sequence(mut context: TestContext, mut contract: Contract) {
+        let (initial_sequence, initial_head) = contract.get_audit_head();
+        assert_eq!(initial_sequence, 0);
+        assert_eq!(initial_head.0, vec![0u8; 32]);
+
+        context.switch_to_issuer();
+        contract.spare_balance = 10_000.into();
+        contract.issue(1_000, vec![], None);
+
+        let (sequence, head) = contract.get_audit_head();
+        assert_eq!(sequence, 1);
+        assert_ne!(head.0, vec![0u8; 32]);
+    }
+
+    #[rstest]
+    fn verify_audit_replays_matching_chain(mut context: TestContext, mut contract: Contract) {
+        context.switch_to_issuer();
+        contract.spare_balance = 10_000.into();
+        contract.issue(1_000, vec![], None);
+        contract.issue(2_000, vec![], None);
+
+        let (sequence, head) = contract.get_audit_head();
+
+        let events = vec![
+            LtipEvent::Issue((1_000, vec![])),
+            LtipEvent::Issue((2_000, vec![])),
+        ];
+        let block_heights = vec![0, 0];
+
+        assert!(contract.verify_audit(events, block_heights, Base64VecU8::from(vec![0u8; 32]), 0));
+        assert_eq!(sequence, 2);
+        assert_ne!(head.0, vec![0u8; 32]);
+    }
+
+    #[rstest]
+    fn verify_audit_rejects_tampered_history(mut context: TestContext, mut contract: Contract) {
+        context.switch_to_issuer();
+        contract.spare_balance = 10_000.into();
+        contract.issue(1_000, vec![], None);
+
+        let tampered_events = vec![LtipEvent::Issue((9_999, vec![]))];
+
+        assert!(!contract.verify_audit(
+            tampered_events,
+            vec![0],
+            Base64VecU8::from(vec![0u8; 32]),
+            0
+        ));
+    }
+}
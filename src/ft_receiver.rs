@@ -1,10 +1,15 @@
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 use near_sdk::{
-    env::panic_str, json_types::U128, near, require, serde_json, AccountId, PromiseOrValue,
+    env, env::panic_str, json_types::U128, near, require, serde_json, AccountId, PromiseOrValue,
 };
 
-use crate::{grant::GrantApi, Contract, ContractExt, Role};
-use near_sdk_contract_tools::rbac::Rbac;
+use crate::{
+    auth::{AuthApi, Operation},
+    event::LtipEvent,
+    grant::{GrantApi, Schedule},
+    Contract, ContractExt, Role,
+};
+use near_sdk_contract_tools::pause::Pause;
 
 #[near(serializers = [json])]
 #[serde(tag = "type", content = "data")]
@@ -12,13 +17,22 @@ use near_sdk_contract_tools::rbac::Rbac;
 pub enum FtMessage {
     TopUp,
     Issue(IssueData),
-    Migrate(Vec<(AccountId, u32, U128, U128)>),
+    Migrate(MigrateData),
 }
 
 #[near(serializers = [json])]
 pub struct IssueData {
     pub issue_date: u32,
-    pub grants: Vec<(AccountId, U128)>,
+    pub grants: Vec<(AccountId, U128, Option<Schedule>)>,
+}
+
+#[near(serializers = [json])]
+pub struct MigrateData {
+    /// Unique, monotonically increasing id for this migration batch. Replaying a `batch_id`
+    /// that has already been consumed is rejected, making the predecessor->successor migration
+    /// one-shot and non-replayable.
+    pub batch_id: u64,
+    pub accounts: Vec<(AccountId, u32, U128, U128)>,
 }
 
 #[near]
@@ -29,13 +43,19 @@ impl FungibleTokenReceiver for Contract {
         amount: U128,
         msg: String,
     ) -> PromiseOrValue<U128> {
+        if env::predecessor_account_id() != self.token_id {
+            // Only the configured LTIP token may fund this contract; anything else is returned
+            // in full rather than trusted and processed.
+            return PromiseOrValue::Value(amount);
+        }
+
         let message: FtMessage =
             serde_json::from_str(&msg).unwrap_or_else(|_| panic_str("Failed to parse the message"));
 
         match message {
             FtMessage::TopUp => self.on_top_up(&sender_id, amount),
             FtMessage::Issue(issue_data) => self.on_issue(&sender_id, amount, issue_data),
-            FtMessage::Migrate(accounts) => self.on_migrate(&sender_id, amount, accounts),
+            FtMessage::Migrate(migrate_data) => self.on_migrate(&sender_id, amount, migrate_data),
         }
 
         PromiseOrValue::Value(0.into())
@@ -44,32 +64,57 @@ impl FungibleTokenReceiver for Contract {
 
 impl Contract {
     fn on_top_up(&mut self, sender_id: &AccountId, amount: U128) {
-        Self::has_role(sender_id, &Role::Issuer);
+        require!(
+            self.has_role(sender_id, Role::Issuer),
+            "Only an Issuer may top up spare_balance"
+        );
+        Self::require_unpaused();
 
         self.spare_balance.0 += amount.0;
+
+        self.commit_event(&LtipEvent::TopUp((sender_id.clone(), amount.0)));
     }
 
     fn on_issue(&mut self, sender_id: &AccountId, amount: U128, issue_data: IssueData) {
-        Self::has_role(sender_id, &Role::Issuer);
+        require!(
+            self.has_role(sender_id, Role::Issuer),
+            "Only an Issuer may fund a grant via ft_on_transfer"
+        );
+        Self::require_unpaused();
 
-        let total_amount: u128 = issue_data.grants.iter().map(|(_, amount)| amount.0).sum();
+        let total_amount: u128 = issue_data
+            .grants
+            .iter()
+            .map(|(_, amount, _)| amount.0)
+            .sum();
         require!(
             total_amount == amount.0,
             "Transferred amount doesn't match total grants amount"
         );
 
-        self.issue(issue_data.issue_date, issue_data.grants);
+        self.issue(issue_data.issue_date, issue_data.grants, None);
     }
 
-    fn on_migrate(
-        &mut self,
-        sender_id: &AccountId,
-        amount: U128,
-        accounts: Vec<(AccountId, u32, U128, U128)>,
-    ) {
-        Self::has_role(sender_id, &Role::Predecessor);
+    fn on_migrate(&mut self, sender_id: &AccountId, amount: U128, migrate_data: MigrateData) {
+        require!(
+            self.has_role(sender_id, Role::Predecessor),
+            "Only the configured Predecessor may submit a migration batch"
+        );
+        self.require_operation_unpaused(Operation::Migrate);
 
-        let total_amount: u128 = accounts
+        require!(
+            !self.migration_finalized,
+            "Migration has been finalized, no further batches are accepted"
+        );
+        require!(
+            !self
+                .consumed_migration_batches
+                .contains(&migrate_data.batch_id),
+            "Migration batch has already been applied"
+        );
+
+        let total_amount: u128 = migrate_data
+            .accounts
             .iter()
             .map(|(_, _, total_amount, claimed_amount)| total_amount.0 - claimed_amount.0)
             .sum();
@@ -78,8 +123,215 @@ impl Contract {
             "Transferred amount doesn't match total grants amount"
         );
 
-        for (account_id, issue_date, total_amount, claimed_amount) in accounts.into_iter() {
-            self.create_grant_internal(&account_id, issue_date, total_amount, Some(claimed_amount));
+        self.consumed_migration_batches
+            .insert(migrate_data.batch_id);
+
+        for (account_id, issue_date, total_amount, claimed_amount) in
+            migrate_data.accounts.into_iter()
+        {
+            self.create_grant_internal(
+                &account_id,
+                issue_date,
+                total_amount,
+                Some(claimed_amount),
+                None,
+            );
+        }
+
+        self.commit_event(&LtipEvent::Migrate((
+            sender_id.clone(),
+            migrate_data.batch_id,
+            amount.0,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use near_sdk::{json_types::U128, AccountId, PromiseOrValue};
+    use near_sdk_contract_tools::rbac::Rbac;
+    use rstest::*;
+
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+
+    use crate::{
+        auth::{AuthApi, Role},
+        ft_receiver::MigrateData,
+        tests::context::TestContext,
+        tests::fixtures::*,
+        Contract,
+    };
+
+    fn migrate_predecessor(contract: &mut Contract, context: &mut TestContext) -> AccountId {
+        let predecessor: AccountId = "predecessor.test.near".parse().unwrap();
+        contract.add_role(&predecessor, &Role::Predecessor);
+        context.switch_account(&predecessor);
+        predecessor
+    }
+
+    #[rstest]
+    fn on_migrate_creates_grants_and_consumes_batch(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let predecessor = migrate_predecessor(&mut contract, &mut context);
+
+        contract.on_migrate(
+            &predecessor,
+            U128::from(1_000),
+            MigrateData {
+                batch_id: 0,
+                accounts: vec![(alice.clone(), 1_000, U128::from(2_000), U128::from(1_000))],
+            },
+        );
+
+        assert!(contract.accounts.get(&alice).is_some());
+        assert!(contract.consumed_migration_batches.contains(&0));
+    }
+
+    #[rstest]
+    fn on_migrate_rejects_replayed_batch_id(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let predecessor = migrate_predecessor(&mut contract, &mut context);
+
+        contract.on_migrate(
+            &predecessor,
+            U128::from(1_000),
+            MigrateData {
+                batch_id: 0,
+                accounts: vec![(alice.clone(), 1_000, U128::from(2_000), U128::from(1_000))],
+            },
+        );
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.on_migrate(
+                &predecessor,
+                U128::from(1_000),
+                MigrateData {
+                    batch_id: 0,
+                    accounts: vec![(alice.clone(), 2_000, U128::from(2_000), U128::from(1_000))],
+                },
+            );
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn on_migrate_rejects_after_finalization(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        let predecessor = migrate_predecessor(&mut contract, &mut context);
+
+        context.switch_account(&owner);
+        contract.finalize_migration();
+
+        context.switch_account(&predecessor);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.on_migrate(
+                &predecessor,
+                U128::from(1_000),
+                MigrateData {
+                    batch_id: 0,
+                    accounts: vec![(alice.clone(), 1_000, U128::from(2_000), U128::from(1_000))],
+                },
+            );
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn ft_on_transfer_rejects_unknown_token(
+        mut context: TestContext,
+        mut contract: Contract,
+        issuer: AccountId,
+    ) {
+        let impostor: AccountId = "impostor.test.near".parse().unwrap();
+        context.switch_account(&impostor);
+
+        // The predecessor check short-circuits before the message is ever parsed.
+        let result = contract.ft_on_transfer(issuer, U128::from(1_000), "garbage".to_string());
+
+        match result {
+            PromiseOrValue::Value(returned) => assert_eq!(returned, U128::from(1_000)),
+            PromiseOrValue::Promise(_) => {
+                panic!("expected rejection to return a value, not a promise")
+            }
         }
+        assert_eq!(contract.spare_balance, U128::from(0));
+    }
+
+    #[rstest]
+    fn on_top_up_requires_issuer_role(mut context: TestContext, mut contract: Contract) {
+        let impostor: AccountId = "impostor.test.near".parse().unwrap();
+        context.switch_account(&impostor);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.on_top_up(&impostor, U128::from(1_000));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(contract.spare_balance, U128::from(0));
+    }
+
+    #[rstest]
+    fn on_issue_requires_issuer_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        use crate::ft_receiver::IssueData;
+
+        let impostor: AccountId = "impostor.test.near".parse().unwrap();
+        context.switch_account(&impostor);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.on_issue(
+                &impostor,
+                U128::from(1_000),
+                IssueData {
+                    issue_date: 1_000,
+                    grants: vec![(alice.clone(), U128::from(1_000), None)],
+                },
+            );
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.accounts.get(&alice).is_none());
+    }
+
+    #[rstest]
+    fn on_migrate_requires_predecessor_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let impostor: AccountId = "impostor.test.near".parse().unwrap();
+        context.switch_account(&impostor);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.on_migrate(
+                &impostor,
+                U128::from(1_000),
+                MigrateData {
+                    batch_id: 0,
+                    accounts: vec![(alice.clone(), 1_000, U128::from(2_000), U128::from(1_000))],
+                },
+            );
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.accounts.get(&alice).is_none());
+        assert!(!contract.consumed_migration_batches.contains(&0));
     }
 }
@@ -4,8 +4,10 @@ use std::{
 };
 
 use crate::{
-    common::{assert_gas, now},
+    auth::{AuthApi, Operation},
+    common::{assert_nonzero_amount, now, GasBudget, PessimisticGasEstimator},
     event::{LtipEvent, OrderUpdateData},
+    storage::STORAGE_PRICE_PER_BYTE,
     vesting::calculate_vested_amount,
     Account, Config, Contract, ContractExt, Grant, Role,
 };
@@ -15,29 +17,175 @@ use near_sdk::{
     near, require, serde_json, AccountId, NearToken, Promise, PromiseResult,
 };
 use near_sdk_contract_tools::{
-    ft::nep141::GAS_FOR_FT_TRANSFER_CALL, pause::Pause, rbac::Rbac, standard::nep297::Event,
+    ft::nep141::GAS_FOR_FT_TRANSFER_CALL, owner::OwnerExternal, pause::Pause, rbac::Rbac,
+    standard::nep297::Event,
 };
 
-const GAS_FOR_CALLBACK: near_sdk::Gas = near_sdk::Gas::from_tgas(5);
+/// Reserved for `on_authorize_complete`, the resolve callback an `authorize` batch's
+/// `ft_transfer`s are `.then()`-chained to.
+const GAS_FOR_RESOLVE_TRANSFER: near_sdk::Gas = near_sdk::Gas::from_tgas(5);
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-#[near(serializers = [json])]
+/// How many blocks an `authorize` batch's lock is given to resolve normally via
+/// `on_authorize_complete` (at NEAR's ~1s block time, roughly one hour) before
+/// `reconcile_pending_transfer` is willing to act on it.
+const STUCK_TRANSFER_BLOCKS: u64 = 3_600;
+
+/// Pessimistic per-account compute cost of `buy`'s matching loop (checking reserve price,
+/// journaling, and updating balances for every grant an account holds), used by
+/// `PessimisticGasEstimator` to pre-check or cursor through a batch instead of running out of
+/// gas mid-loop.
+const GAS_PER_BUY_ACCOUNT: near_sdk::Gas = near_sdk::Gas::from_tgas(2);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[near(serializers = [borsh, json])]
 pub struct TransferKey {
     pub account_id: AccountId,
     pub issue_at: u32,
 }
 
+/// Snapshots each touched grant in full immediately before a batch (e.g. an atomic `buy`, or
+/// `authorize`'s `require_all` locking pass) mutates it, so the batch can be undone as a unit if
+/// part of it turns out to be ineligible, rather than reverting key-by-key.
+#[derive(Default)]
+struct Journal {
+    entries: Vec<(TransferKey, Grant)>,
+}
+
+impl Journal {
+    fn checkpoint(&mut self, transfer_key: TransferKey, grant: &Grant) {
+        self.entries.push((transfer_key, grant.clone()));
+    }
+
+    /// Keeps every mutation recorded since the last checkpoint by simply dropping the journal.
+    fn commit(self) {}
+
+    /// Restores every journaled grant to its pre-batch snapshot.
+    fn revert(self, contract: &mut Contract) {
+        for (transfer_key, grant) in self.entries {
+            if let Some(account) = contract.accounts.get_mut(&transfer_key.account_id) {
+                account.grants.insert(transfer_key.issue_at, grant);
+            }
+        }
+    }
+}
+
+/// Reasons a `GrantApi` call may be rejected. The per-account variants (`NothingToClaim`,
+/// `InsufficientOrder`, `AlreadyTerminated`) are skips: `claim`/`buy`/`terminate` record them in
+/// `Contract::last_error` and move on to the next account instead of panicking, so an off-chain
+/// caller can query why a batch entry did nothing instead of having to re-derive it from on-chain
+/// state. `Unauthorized`/`ContractPaused` instead gate the call before any account is even
+/// looked at, via `Contract::try_require_role_active`/`try_require_operation_unpaused`; those
+/// still reject the whole transaction, but as a typed `GrantError` converted to a panic at the
+/// `#[near]` boundary (`Contract::panic_on_grant_error`) rather than an ad-hoc `require!` string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GrantError {
+    /// Nothing vested and unclaimed was found to move into the caller's order.
+    NothingToClaim,
+    /// The account's orders were all empty, locked, or condition-gated, so there was nothing
+    /// for `buy` to fill.
+    InsufficientOrder,
+    /// The account's grant at the given `issue_at` was already terminated.
+    AlreadyTerminated,
+    /// The caller lacks the role (or their time-bounded grant of it has expired) this entry
+    /// point requires.
+    Unauthorized,
+    /// The contract (or the specific `Operation` this entry point gates) is currently paused.
+    ContractPaused,
+}
+
+impl std::fmt::Display for GrantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            GrantError::NothingToClaim => "Nothing vested and unclaimed to claim",
+            GrantError::InsufficientOrder => "No fillable order for this account",
+            GrantError::AlreadyTerminated => "Grant has already been terminated",
+            GrantError::Unauthorized => "Caller does not hold the required role",
+            GrantError::ContractPaused => "Operation is currently paused",
+        };
+        write!(f, "{message}")
+    }
+}
+
 #[near(serializers = [json])]
 pub struct AccountView {
     pub account_id: AccountId,
     pub grants: Vec<GrantView>,
 }
 
+/// A single grant's locked-but-unsettled transfer, as returned by `get_pending_transfers`.
+#[near(serializers = [json])]
+pub struct PendingTransfer {
+    pub issue_at: u32,
+    pub amount: U128,
+    pub authorized_bps: u32,
+}
+
+/// A single grant whose `authorize` transfer most recently failed, as returned by
+/// `get_failed_transfers`. `order_amount` already has `amount` restored by
+/// `on_authorize_complete`; this entry just remembers the failure (and the `authorized_bps` it
+/// failed at) until `retry_failed` re-authorizes it or another `authorize` batch touches the
+/// same grant again.
+#[near(serializers = [json])]
+pub struct FailedTransfer {
+    pub issue_at: u32,
+    pub amount: U128,
+    pub authorized_bps: u32,
+}
+
+/// Controls how `issue` handles a batch whose requested `sum(amount)` exceeds the available
+/// `spare_balance`, rather than always rejecting it outright.
+#[near(serializers = [json])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FillPolicy {
+    /// Rejects the whole batch unless it can be funded in full. The default.
+    AllOrNothing,
+    /// Scales every grant down by the same `available / total` ratio (floored), handing the
+    /// rounding remainder one unit at a time to the lowest `AccountId`s first so the outcome is
+    /// independent of input order and `spare_balance` lands at exactly zero.
+    ProRata,
+    /// Funds grants in the caller-supplied order until `spare_balance` runs out. A grant that
+    /// can't be funded in full is skipped entirely rather than partially filled.
+    PriorityOrder,
+}
+
+/// Per-account outcome of a single `issue` call: what was requested versus what actually got
+/// funded, for callers to reconcile any shortfall a `FillPolicy` other than `AllOrNothing` left.
+#[near(serializers = [json])]
+pub struct IssueReport {
+    pub account_id: AccountId,
+    pub requested_amount: U128,
+    pub issued_amount: U128,
+}
+
+/// A single outstanding order, as returned by `get_orders`.
+#[near(serializers = [json])]
+pub struct Order {
+    pub account_id: AccountId,
+    pub issue_at: u32,
+    pub amount: U128,
+    pub vested_amount: U128,
+    pub reserve_bps: Option<u32>,
+}
+
+/// Narrows the orders `get_orders` returns, so an executor can page through or target a large
+/// order set instead of always pulling every account's orders.
+#[near(serializers = [json])]
+#[derive(Default)]
+pub struct OrderFilter {
+    /// Only orders with `amount >= min_amount` are returned.
+    pub min_amount: Option<U128>,
+    /// Restricts the search to these accounts. `None` searches every account.
+    pub accounts: Option<Vec<AccountId>>,
+    /// Only orders whose `amount` fits within the contract's current `spare_balance` are
+    /// returned, i.e. orders an executor could actually fund right now.
+    pub payable_only: bool,
+}
+
 #[near(serializers = [json])]
 pub struct GrantView {
     pub issued_at: u32,
-    pub cliff_end_at: u32,
-    pub vesting_end_at: u32,
+    pub cliff_end_at: Option<u32>,
+    pub vesting_end_at: Option<u32>,
     pub total_amount: U128,
     pub claimed_amount: U128,
     pub order_amount: U128,
@@ -45,6 +193,58 @@ pub struct GrantView {
     pub not_vested_amount: U128,
     pub claimable_amount: U128,
     pub terminated_at: Option<u32>,
+    /// Release conditions not yet cleared by `apply_witness`. Empty once every predicate this
+    /// grant was issued with has been satisfied.
+    pub conditions: Vec<Condition>,
+}
+
+/// How a grant's `total_amount` unlocks over time.
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub enum Schedule {
+    /// The original cliff-then-linear curve, anchored to absolute timestamps.
+    Linear { cliff_end: u32, vesting_end: u32 },
+    /// A set of fixed-amount tranches, each unlocking once its time and (optional) approver
+    /// conditions are both satisfied.
+    Milestone { tranches: Vec<Tranche> },
+}
+
+/// A single milestone unlock within a `Schedule::Milestone`.
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct Tranche {
+    pub amount: U128,
+    pub unlock_after: u32,
+    pub approver: Option<AccountId>,
+    pub released: bool,
+}
+
+/// An additional release condition a grant's `order_amount` must clear before `claim`/`buy`
+/// will act on it, beyond the time cliff. A grant is releasable once every condition in its
+/// `Grant::conditions` has been removed. Conditions are only re-checked by `apply_witness`, not
+/// by every `claim`/`buy`, so a satisfied `Timestamp` still needs one witness call to clear it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[near(serializers = [borsh, json])]
+pub enum Condition {
+    /// Satisfied once `block_timestamp >= seconds`. Anyone may witness it.
+    Timestamp(u32),
+    /// Satisfied only when `reporter` itself calls `apply_witness` for this grant.
+    Oracle { reporter: AccountId },
+}
+
+impl Schedule {
+    /// Builds a `Linear` schedule from durations relative to `issue_at`, the same way the
+    /// global `Config` curve is derived, so a single grant can override the cliff/vesting
+    /// duration it's issued under (e.g. an executive or retention grant) without touching
+    /// every other beneficiary's terms.
+    pub fn linear(issue_at: u32, cliff_duration: u32, vesting_duration: u32) -> Self {
+        let cliff_end = issue_at + cliff_duration;
+
+        Schedule::Linear {
+            cliff_end,
+            vesting_end: cliff_end + vesting_duration,
+        }
+    }
 }
 
 /// GrantApi encapsulates vesting-related actions such as claiming, issuing, buybacks, and termination logic.
@@ -52,69 +252,267 @@ pub trait GrantApi {
     /// Processes the caller's grants and accrues any newly unlocked amounts into their order balance.
     fn claim(&mut self);
 
-    /// Authorizes payment for outstanding orders on the supplied accounts using an optional basis-point percentage.
-    fn authorize(&mut self, account_ids: Vec<AccountId>, percentage: Option<u32>);
+    /// Sets the minimum basis-point buyback price the caller will accept for their `issue_at`
+    /// grant's outstanding order. `None` accepts any price `buy` offers.
+    fn set_order_reserve(&mut self, issue_at: u32, min_buy_bps: Option<u32>);
 
-    /// Callback invoked after batched FT transfers to reconcile pending transfers with on-chain state.
-    fn on_authorize_complete(&mut self, transfer_keys: Vec<TransferKey>);
+    /// Authorizes payment for outstanding orders on the supplied accounts using an optional basis-point percentage.
+    /// Locks each touched grant (`Grant::locked`) and moves its order into `pending_amount`
+    /// before the batched `ft_transfer` promise resolves, so unrelated grants (and other
+    /// accounts' `claim`/`buy`/`terminate` calls) are unaffected while the batch is in flight.
+    ///
+    /// If `require_all` is `false` (the default/independent mode), every account with at least
+    /// one fillable order is locked and transferred regardless of any other account in the batch
+    /// having nothing to authorize. If `require_all` is `true`, the locking pass is journaled
+    /// (`Journal::checkpoint`) as it goes; the first account with no fillable order rolls every
+    /// grant locked so far in this batch back via `Journal::revert` and the call returns without
+    /// dispatching any transfer, recording `GrantError::InsufficientOrder` via `last_error` for
+    /// every account in the batch. This only governs whether the batch is submitted atomically —
+    /// once the `ft_transfer` promises are dispatched, each one's success or failure is still
+    /// reconciled independently by `on_authorize_complete`, since a transfer that already
+    /// succeeded moved real tokens and can't be undone by rolling back local bookkeeping.
+    ///
+    /// If a `FeeSchedule` is configured with a nonzero `authorize_bps`, each grant's computed
+    /// `order_amount * percentage` still moves out of `order_amount` in full (so `claimed_amount`
+    /// keeps tracking the whole settled slice once the batch resolves), but the *transfer* sent
+    /// to the grantee is that amount less its floored `authorize_bps` cut; the accumulated cut is
+    /// credited to `accrued_fees` and flushed as one extra `ft_transfer` leg on this same batch,
+    /// to `fee_collector`, settled by `on_authorize_complete` alongside the grantee legs. With no
+    /// fee schedule (or `authorize_bps == 0`) this cut is always zero, so a plain `authorize` call
+    /// behaves exactly as before.
+    fn authorize(
+        &mut self,
+        account_ids: Vec<AccountId>,
+        percentage: Option<u32>,
+        require_all: bool,
+    );
+
+    /// Callback invoked after batched FT transfers to reconcile each locked grant with on-chain
+    /// state: successful transfers credit `claimed_amount`, failed ones restore `order_amount`
+    /// and are recorded via `LtipEvent::TransferReverted`. Unlocks every touched grant.
+    /// `gas_per_transfer` is the gas each transfer in the batch was dispatched with, passed
+    /// through from `authorize`'s `GasBudget` split for diagnostic logging only — `GasBudget`
+    /// already guarantees it's never less than `GAS_FOR_FT_TRANSFER_CALL`, so a failed transfer
+    /// here always reflects the receiver's own logic (e.g. an unregistered account), never a gas
+    /// shortfall; there's nothing to classify. `fee_amount` is what `authorize` flushed
+    /// out of `accrued_fees` as this batch's trailing fee-collector leg (`0` if nothing was
+    /// flushed) — its `promise_result` lives one index past the last `transfer_key`; on failure
+    /// the flushed amount is credited back to `accrued_fees` for a later batch to retry, recorded via `LtipEvent::FeeTransferReverted` the same way a failed grantee leg is recorded via `LtipEvent::TransferReverted`.
+    ///
+    /// Reverts are exact by construction rather than by replaying a snapshot: `authorize` never
+    /// discards the amount it moved out of `order_amount`, it parks it in that grant's own
+    /// `pending_amount`, so restoring it here is just adding that same value back. Each
+    /// `transfer_key` is resolved off its own `promise_result` index, independent of every other
+    /// transfer in the batch, so a partial failure (or callbacks arriving out of order) can't
+    /// corrupt a sibling grant's reconciliation the way replaying a shared batch-wide checkpoint
+    /// could if two in-flight batches ever touched the same grant.
+    fn on_authorize_complete(
+        &mut self,
+        transfer_keys: Vec<TransferKey>,
+        gas_per_transfer: u64,
+        fee_amount: U128,
+    );
 
     /// Issues grants for the specified timestamp, reducing spare balance accordingly.
-    fn issue(&mut self, issue_at: u32, grants: Vec<(AccountId, U128)>);
+    /// A `None` schedule defaults to the cliff+linear curve driven by the global `Config`.
+    /// The measured storage growth of the new `Grant` entries is charged against the attached
+    /// deposit first and each grantee's registered `storage_deposits` balance after, per
+    /// `Contract::charge_storage`; any unused attached deposit is refunded to the caller.
+    ///
+    /// `fill_policy` (default `FillPolicy::AllOrNothing`) governs what happens when the batch's
+    /// requested `sum(amount)` exceeds the available `spare_balance`: see `FillPolicy` for the
+    /// three behaviors. Returns an `IssueReport` per account so a caller using `ProRata` or
+    /// `PriorityOrder` can reconcile what actually got funded against what it asked for.
+    fn issue(
+        &mut self,
+        issue_at: u32,
+        grants: Vec<(AccountId, U128, Option<Schedule>)>,
+        fill_policy: Option<FillPolicy>,
+    ) -> Vec<IssueReport>;
 
     /// Executes a buyback against the provided accounts by the given percentage (basis points).
-    fn buy(&mut self, account_ids: Vec<AccountId>, percentage: u32);
+    /// An order whose grant has set a `min_buy_bps` reserve above `percentage` is skipped (its
+    /// `order_amount` left intact) rather than force-filled at a price the grantee rejected.
+    /// Each grant is considered at most once per call even if `account_ids` lists the same
+    /// account more than once, so a repeated account can't have its order bought back twice (or,
+    /// in `atomic` mode, journaled against an already-dirty snapshot).
+    ///
+    /// `buy` only mutates `order_amount`/`claimed_amount`/`spare_balance` in contract storage; it
+    /// never creates a `Promise`. Orders `buy` skips (below reserve, locked, or condition-gated)
+    /// keep their `order_amount`, which is paid out through the separate
+    /// `authorize`/`on_authorize_complete` pipeline — that's where the real `ft_transfer` and its
+    /// on-chain failure rollback live.
+    ///
+    /// If a `FeeSchedule` is configured with a nonzero `buyback_bps`, each fill's floored cut of
+    /// the bought-back amount is credited to `accrued_fees` instead of the grantee's
+    /// `claimed_amount`. Since `buy` never creates a `Promise` to settle that cut through, it
+    /// just accrues until the next `authorize` batch flushes it (see `on_authorize_complete`).
+    ///
+    /// If `atomic` is `false` (the default/independent mode), every grant that clears its
+    /// reserve is filled regardless of any other grant in the batch being skipped. If `atomic`
+    /// is `true`, a single skip rolls the entire batch back via `Journal::revert`, so the
+    /// executor either fills every eligible order in the batch or none of them.
+    ///
+    /// `max_spend`, if set, caps the total `claimed_amount` this call moves out of `spare_balance`.
+    /// Eligible orders are filled cheapest-first (ascending `min_buy_bps`, with no reserve sorting
+    /// first) so a capped budget funds the most price-accommodating grantees before the pickier
+    /// ones; the order that exhausts the budget is filled partially rather than skipped, and any
+    /// orders after it are returned unprocessed just as a gas-exhausted tail would be. In `atomic`
+    /// mode a `max_spend` too small to cover every eligible order at once is refused up front
+    /// (alongside the existing gas pre-check) rather than left to partially fill, since atomic
+    /// means all-or-nothing.
+    ///
+    /// Pessimistically pre-checks (`PessimisticGasEstimator`) whether the batch can run to
+    /// completion within the gas actually left. An atomic batch refuses to start at all if it
+    /// can't, since a partial pass would have nothing coherent to roll back to; a non-atomic
+    /// batch instead stops as soon as the remaining accounts stop being affordable and returns
+    /// them unprocessed, so the caller can resume from there instead of the call failing
+    /// destructively mid-loop.
+    fn buy(
+        &mut self,
+        account_ids: Vec<AccountId>,
+        percentage: u32,
+        atomic: bool,
+        max_spend: Option<U128>,
+    ) -> Vec<AccountId>;
 
-    /// Returns all outstanding orders (account, issue date, order amount).
-    fn get_orders(&self) -> Vec<(AccountId, u32, U128)>;
+    /// Returns outstanding orders matching `filter`, letting an executor page through or target
+    /// a large order set instead of always pulling every account's orders.
+    fn get_orders(&self, filter: OrderFilter) -> Vec<Order>;
 
     /// Retrieves a copy of the stored account, if present.
     fn get_account(&self, account_id: &AccountId) -> Option<AccountView>;
 
+    /// Returns the effective `(cliff_end, vesting_end)` for a single grant, resolving its
+    /// per-grant `Schedule::Linear` override if present. Returns `None` for a missing grant or
+    /// one that uses a `Schedule::Milestone` (which has no cliff/vesting-end concept).
+    fn get_grant_schedule(&self, account_id: &AccountId, issue_at: u32) -> Option<(u32, u32)>;
+
+    /// Custodian-gated renegotiation of a single grant's `Schedule::Linear` lockup, following
+    /// the Solana stake `LockupArgs` custodian model: `new_schedule` may only extend
+    /// `cliff_end`/`vesting_end`, never shorten either, so the foundation can relax a lockup
+    /// without re-issuing the grant (or secretly clawing back vesting that already applied).
+    /// Panics if the grant (or either schedule) isn't `Schedule::Linear`, since a
+    /// `Schedule::Milestone` grant has no single cliff/vesting-end to extend.
+    fn update_lockup(&mut self, account_id: AccountId, issue_at: u32, new_schedule: Schedule);
+
     /// Returns the contract's spare balance.
     fn get_spare_balance(&self) -> U128;
 
-    /// Returns a copy of the pending transfers accumulated during authorization flow.
-    fn get_pending_transfers(&self) -> HashMap<AccountId, Vec<(u32, U128)>>;
-
-    /// Terminates an account's grants at the provided timestamp, adjusting totals to reflect vested amounts.
+    /// Returns the running total of unvested tokens `terminate`/`terminate_vesting` have ever
+    /// clawed back into `spare_balance`, for audit/reporting purposes.
+    fn get_forfeited_total(&self) -> U128;
+
+    /// Returns the currently locked grants accumulated by an in-flight `authorize` batch, keyed
+    /// by grantee. If a `FeeSchedule` is configured and `accrued_fees` is nonzero, also reports
+    /// one aggregated entry (`issue_at: 0`, since it isn't tied to any single grant) keyed by
+    /// `fee_collector` for the commission `authorize`/`buy` have collected but not yet flushed.
+    fn get_pending_transfers(&self) -> HashMap<AccountId, Vec<PendingTransfer>>;
+
+    /// Recovers a grant left locked by a `batch_id` whose `on_authorize_complete` callback never
+    /// ran (e.g. the promise was dropped or ran out of gas), restoring `pending_amount` back
+    /// into `order_amount` exactly as a failed transfer would be reverted. No-ops (and is safe
+    /// to call more than once) unless the grant is still locked under `batch_id` and has sat
+    /// past `STUCK_TRANSFER_BLOCKS` without the normal callback clearing it.
+    fn reconcile_pending_transfer(&mut self, account_id: AccountId, issue_at: u32, batch_id: u64);
+
+    /// Returns every grant whose `authorize` transfer most recently failed, keyed by grantee.
+    /// `order_amount` already has each entry's amount restored; these are just the ones
+    /// `retry_failed` (or operator judgment) still has to act on.
+    fn get_failed_transfers(&self) -> HashMap<AccountId, Vec<FailedTransfer>>;
+
+    /// Re-authorizes every grant of `account_ids` currently recorded in `get_failed_transfers`,
+    /// grouped and re-dispatched through `authorize` at the same `authorized_bps` each failed
+    /// at (one `authorize` call per distinct bps value among them). Clears the retried entries
+    /// from the failed set up front; a retry that fails again re-adds itself via the normal
+    /// `on_authorize_complete` failure path.
+    fn retry_failed(&mut self, account_ids: Vec<AccountId>);
+
+    /// Foundation clawback: stops future vesting on every one of `account_id`'s grants not
+    /// already terminated, using the same `get_vested_amount` cliff/schedule math `claim` relies
+    /// on. Each grant's `total_amount` drops to what's vested as of `timestamp` (crediting the
+    /// difference back to `spare_balance` and adding it to `total_forfeited`), `order_amount` is
+    /// trimmed down to whatever of that vested amount is still unclaimed (so
+    /// already-earned-but-unordered tokens stay claimable,
+    /// while any order that projected further vesting than actually happened is cut back), and
+    /// `claimed_amount` is left untouched. Idempotent per grant — a grant with `terminated_at`
+    /// already set is skipped, so a second call (or a call covering grants terminated in an
+    /// earlier batch) is a no-op for them. Records `GrantError::AlreadyTerminated` via
+    /// `last_error` if nothing was newly terminated.
     fn terminate(&mut self, account_id: AccountId, timestamp: u32);
+
+    /// Narrower form of `terminate` that targets exactly one of `account_id`'s grants (by
+    /// `issue_at`) as of right now, instead of every grant the account holds. Useful when an
+    /// account has more than one grant and only one of them (e.g. a since-superseded award)
+    /// should be revoked. Shares `terminate`'s math and staking-recall behavior and is
+    /// idempotent in the same way: a missing grant, an already-terminated grant, or one
+    /// currently `locked` by an in-flight `authorize` batch records
+    /// `GrantError::AlreadyTerminated` via `last_error` instead of panicking.
+    fn terminate_vesting(&mut self, account_id: AccountId, issue_at: u32);
+
+    /// Marks a milestone tranche as released. If the tranche has a designated `approver`,
+    /// only that account may confirm it; the tranche still only counts as vested once
+    /// `block_timestamp >= unlock_after`.
+    fn confirm_tranche(&mut self, account_id: AccountId, issue_at: u32, tranche_index: u32);
+
+    /// Clears every `Grant::conditions` entry the caller and the current block can satisfy
+    /// right now: a `Condition::Timestamp` once `block_timestamp` has passed it, or a
+    /// `Condition::Oracle` if the caller is its designated `reporter`. Once the grant's
+    /// condition set is empty, `claim`/`buy` treat it like any other unlocked grant.
+    fn apply_witness(&mut self, account_id: AccountId, issue_at: u32);
+
+    /// Returns the reason `claim`/`buy`/`terminate` most recently skipped `account_id` without
+    /// panicking, if any. Cleared the next time that account's batch entry succeeds.
+    fn get_last_error(&self, account_id: AccountId) -> Option<String>;
+
+    /// Removes fully-settled grants from each of `account_ids` and deletes any account whose
+    /// grant map becomes empty as a result, refunding the freed storage-staking deposit to the
+    /// contract owner. A grant is settled once it holds no outstanding `order_amount`, isn't
+    /// `locked` by an in-flight `authorize` batch, and has at most `dust_threshold` left between
+    /// `total_amount` and `claimed_amount` (letting a non-zero remainder too small to ever be
+    /// claimed be collapsed along with an exactly-finished grant).
+    fn prune(&mut self, account_ids: Vec<AccountId>, dust_threshold: U128);
 }
 
 #[near]
 impl GrantApi for Contract {
     fn claim(&mut self) {
-        Self::require_unpaused();
-
-        let caller = env::predecessor_account_id();
+        Self::panic_on_grant_error(Self::try_require_unpaused());
 
-        let pending_issue_ats: HashSet<u32> = self
-            .pending_transfers
-            .get(&caller)
-            .map(|transfers| transfers.iter().map(|(date, _)| *date).collect())
-            .unwrap_or_default();
+        let caller = self.resolve_claim_caller();
 
         let account = self.accounts.entry(caller.clone()).or_insert(Account {
             grants: Default::default(),
         });
 
         if account.grants.is_empty() {
+            self.last_error
+                .insert(caller, GrantError::NothingToClaim.to_string());
             return;
         }
 
         let mut event_data = vec![];
 
         for (issue_at, grant) in account.grants.iter_mut() {
-            if pending_issue_ats.contains(issue_at) {
+            if grant.locked || !grant.conditions.is_empty() {
                 continue;
             }
 
-            let vested_amount = grant.get_vested_amount(*issue_at, &self.config);
-
-            if vested_amount == 0 {
+            let vested_amount = grant.get_vested_amount();
+            // Excludes `staked_amount`: that portion is delegated to a staking pool via
+            // `stake_idle` and isn't liquid. Claiming it into `order_amount` here would let
+            // `buy`/`authorize` try to pay it out before an `unstake` call has actually
+            // recalled it. The executor must `unstake` a grant first to make its staked
+            // balance claimable again.
+            let claimable_amount = vested_amount
+                .saturating_sub(grant.claimed_amount.0)
+                .saturating_sub(grant.staked_amount.0);
+
+            if claimable_amount == 0 {
                 continue;
             }
 
-            grant.order_amount.0 = vested_amount - grant.claimed_amount.0;
+            grant.order_amount.0 = claimable_amount;
 
             event_data.push(OrderUpdateData {
                 issue_at: issue_at.clone(),
@@ -122,14 +520,38 @@ impl GrantApi for Contract {
             });
         }
 
-        LtipEvent::OrderUpdate(event_data).emit();
+        if event_data.is_empty() {
+            self.last_error
+                .insert(caller, GrantError::NothingToClaim.to_string());
+        } else {
+            self.last_error.remove(&caller);
+            self.commit_event(&LtipEvent::OrderUpdate(event_data));
+        }
     }
 
-    fn authorize(&mut self, account_ids: Vec<AccountId>, percentage: Option<u32>) {
-        Self::require_role(&Role::Executor);
-        Self::require_unpaused();
+    fn set_order_reserve(&mut self, issue_at: u32, min_buy_bps: Option<u32>) {
+        if let Some(min_buy_bps) = min_buy_bps {
+            require!(min_buy_bps <= 10_000, "min_buy_bps cannot exceed 10_000");
+        }
+
+        let caller = env::predecessor_account_id();
+        let grant = self
+            .accounts
+            .get_mut(&caller)
+            .and_then(|account| account.grants.get_mut(&issue_at))
+            .unwrap_or_else(|| panic_str("No grant found for the caller at the given issue date"));
+
+        grant.min_buy_bps = min_buy_bps;
+    }
 
-        self.pause();
+    fn authorize(
+        &mut self,
+        account_ids: Vec<AccountId>,
+        percentage: Option<u32>,
+        require_all: bool,
+    ) {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Executor));
+        Self::panic_on_grant_error(Self::try_require_unpaused());
 
         let percentage = percentage.unwrap_or(10_000);
         if percentage == 0 {
@@ -137,22 +559,27 @@ impl GrantApi for Contract {
             return;
         }
 
-        self.pending_transfers.clear();
+        self.next_batch_id += 1;
+        let batch_id = self.next_batch_id;
+
         let mut transfers = Vec::new();
         let mut transfer_keys = Vec::new();
+        let mut journal = Journal::default();
+        let attempted_accounts = account_ids.clone();
+        let mut ineligible = false;
+        let authorize_fee_bps = self
+            .fee_schedule
+            .as_ref()
+            .map(|fee_schedule| fee_schedule.authorize_bps)
+            .unwrap_or(0);
+        let mut batch_fee_total: u128 = 0;
 
         for account_id in account_ids {
-            let pending_issue_ats: HashSet<u32> = self
-                .pending_transfers
-                .get(&account_id)
-                .map(|transfers| transfers.iter().map(|(date, _)| *date).collect())
-                .unwrap_or_default();
+            let mut locked_any = false;
 
             if let Some(account) = self.accounts.get_mut(&account_id) {
-                let mut account_transfers = Vec::new();
-
                 for (issue_at, grant) in account.grants.iter_mut() {
-                    if pending_issue_ats.contains(issue_at) {
+                    if grant.locked {
                         continue;
                     }
 
@@ -166,32 +593,66 @@ impl GrantApi for Contract {
                         continue;
                     }
 
-                    grant.claimed_amount = U128::from(grant.claimed_amount.0 + authorized_amount);
-                    transfers.push((account_id.clone(), authorized_amount));
+                    if require_all {
+                        journal.checkpoint(
+                            TransferKey {
+                                account_id: account_id.clone(),
+                                issue_at: *issue_at,
+                            },
+                            grant,
+                        );
+                    }
+
+                    let fee_amount = (authorized_amount * authorize_fee_bps as u128) / 10_000;
+                    let grantee_amount = authorized_amount - fee_amount;
+
+                    grant.locked = true;
+                    grant.pending_amount = U128::from(authorized_amount);
+                    grant.order_amount = U128::from(0);
+                    grant.locked_batch_id = Some(batch_id);
+                    grant.locked_at_block_height = Some(env::block_height());
+                    grant.locked_bps = Some(percentage);
+
+                    batch_fee_total += fee_amount;
+                    transfers.push((account_id.clone(), grantee_amount));
                     transfer_keys.push(TransferKey {
                         account_id: account_id.clone(),
                         issue_at: *issue_at,
                     });
-                    account_transfers.push((*issue_at, U128::from(authorized_amount)));
-                    grant.order_amount = U128::from(0);
+                    locked_any = true;
                 }
+            }
 
-                if !account_transfers.is_empty() {
-                    self.pending_transfers
-                        .insert(account_id.clone(), account_transfers);
-                }
+            if require_all && !locked_any {
+                ineligible = true;
+            }
+        }
+
+        if require_all && ineligible {
+            journal.revert(self);
+            for account_id in &attempted_accounts {
+                self.last_error.insert(
+                    account_id.clone(),
+                    GrantError::InsufficientOrder.to_string(),
+                );
             }
+            return;
         }
+        journal.commit();
+        self.accrued_fees.0 += batch_fee_total;
 
         if transfers.is_empty() {
             return;
         }
 
-        assert_gas(
-            (GAS_FOR_FT_TRANSFER_CALL.saturating_add(GAS_FOR_CALLBACK)).as_gas()
-                * transfers.len() as u64,
-            || "Transfer on `authorize` call.",
-        );
+        let fee_payout = self.accrued_fees.0;
+        let dynamic_calls = transfers.len() as u64 + u64::from(fee_payout > 0);
+
+        let per_transfer_gas = GasBudget::new()
+            .reserve(GAS_FOR_RESOLVE_TRANSFER)
+            .split_remaining(dynamic_calls, GAS_FOR_FT_TRANSFER_CALL, || {
+                "Transfer on `authorize` call."
+            });
 
         let mut batch_promise = Promise::new(self.token_id.clone());
         for (account_id, amount) in transfers {
@@ -203,100 +664,189 @@ impl GrantApi for Contract {
                 }))
                 .unwrap(),
                 NearToken::from_yoctonear(1),
-                GAS_FOR_FT_TRANSFER_CALL,
+                per_transfer_gas,
+            );
+        }
+
+        if fee_payout > 0 {
+            let fee_collector = self.fee_schedule.as_ref().unwrap().fee_collector.clone();
+            batch_promise = batch_promise.function_call(
+                "ft_transfer".to_string(),
+                serde_json::to_vec(&serde_json::json!({
+                    "receiver_id": fee_collector,
+                    "amount": fee_payout.to_string()
+                }))
+                .unwrap(),
+                NearToken::from_yoctonear(1),
+                per_transfer_gas,
             );
+            self.accrued_fees = U128::from(0);
         }
 
         batch_promise.then(
             Promise::new(env::current_account_id()).function_call(
                 "on_authorize_complete".to_string(),
                 serde_json::to_vec(&serde_json::json!({
-                    "transfer_keys": transfer_keys
+                    "transfer_keys": transfer_keys,
+                    "gas_per_transfer": per_transfer_gas.as_gas(),
+                    "fee_amount": U128::from(fee_payout)
                 }))
                 .unwrap(),
                 NearToken::from_yoctonear(0),
-                GAS_FOR_CALLBACK,
+                GAS_FOR_RESOLVE_TRANSFER,
             ),
         );
     }
 
     #[private]
-    fn on_authorize_complete(&mut self, transfer_keys: Vec<TransferKey>) {
+    fn on_authorize_complete(
+        &mut self,
+        transfer_keys: Vec<TransferKey>,
+        gas_per_transfer: u64,
+        fee_amount: U128,
+    ) {
         log_str(&format!(
-            "Authorize batch completed: {} transfers processed",
-            transfer_keys.len()
+            "Authorize batch completed: {} transfers processed, {} gas attached per transfer",
+            transfer_keys.len(),
+            gas_per_transfer
         ));
-        Self::require_paused();
+
+        let mut reverted = Vec::new();
 
         for (transfer_index, transfer_key) in transfer_keys.iter().enumerate() {
+            let Some(account) = self.accounts.get_mut(&transfer_key.account_id) else {
+                continue;
+            };
+            let Some(grant) = account.grants.get_mut(&transfer_key.issue_at) else {
+                continue;
+            };
+
+            let pending_amount = grant.pending_amount.0;
+            let authorized_bps = grant.locked_bps.unwrap_or(0);
+
             #[allow(unreachable_patterns)]
             match env::promise_result(transfer_index as u64) {
                 PromiseResult::Successful(_) => {
                     log_str(&format!("Transfer {} succeeded", transfer_index));
+
+                    grant.claimed_amount.0 += pending_amount;
+                    self.failed_transfers.remove(transfer_key);
                 }
                 PromiseResult::Failed => {
                     log_str(&format!(
-                        "Transfer {} failed, reverting claimed_amount",
+                        "Transfer {} failed, restoring order_amount",
                         transfer_index
                     ));
 
-                    let failed_amount = self
-                        .pending_transfers
-                        .get(&transfer_key.account_id)
-                        .and_then(|account_transfers| {
-                            account_transfers
-                                .iter()
-                                .find(|(issue_at, _)| issue_at == &transfer_key.issue_at)
-                                .map(|(_, amount)| amount.0)
-                        });
-
-                    if let Some(amount) = failed_amount {
-                        if let Some(account) = self.accounts.get_mut(&transfer_key.account_id) {
-                            if let Some(grant) = account.grants.get_mut(&transfer_key.issue_at) {
-                                grant.claimed_amount.0 -= amount;
-                                grant.order_amount.0 += amount;
-                            }
-                        }
-                    } else {
-                        log_str(&format!(
-                            "No pending transfer entry for {} at issue date {}",
-                            transfer_key.account_id, transfer_key.issue_at
-                        ));
-                    }
+                    grant.order_amount.0 += pending_amount;
+                    self.failed_transfers.insert(
+                        transfer_key.clone(),
+                        (U128::from(pending_amount), authorized_bps),
+                    );
+                    reverted.push((
+                        transfer_key.account_id.clone(),
+                        transfer_key.issue_at,
+                        pending_amount,
+                    ));
                 }
                 _ => {}
             }
+
+            grant.pending_amount = U128::from(0);
+            grant.locked = false;
+            grant.locked_batch_id = None;
+            grant.locked_at_block_height = None;
+            grant.locked_bps = None;
         }
 
-        self.pending_transfers.clear();
-        self.unpause();
-    }
+        if !reverted.is_empty() {
+            self.commit_event(&LtipEvent::TransferReverted(reverted));
+        }
 
-    fn issue(&mut self, issue_at: u32, grants: Vec<(AccountId, U128)>) {
-        Self::require_role(&Role::Issuer);
+        if fee_amount.0 > 0 {
+            let fee_transfer_index = transfer_keys.len() as u64;
+            if matches!(
+                env::promise_result(fee_transfer_index),
+                PromiseResult::Failed
+            ) {
+                log_str("Fee transfer failed, restoring accrued_fees");
+                self.accrued_fees.0 += fee_amount.0;
+                self.commit_event(&LtipEvent::FeeTransferReverted(fee_amount.0));
+            }
+        }
+    }
 
-        self.issue_internal(issue_at, grants);
+    #[payable]
+    fn issue(
+        &mut self,
+        issue_at: u32,
+        grants: Vec<(AccountId, U128, Option<Schedule>)>,
+        fill_policy: Option<FillPolicy>,
+    ) -> Vec<IssueReport> {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Issuer));
+        Self::panic_on_grant_error(self.try_require_operation_unpaused(Operation::Issue));
+
+        self.issue_internal(
+            issue_at,
+            grants,
+            fill_policy.unwrap_or(FillPolicy::AllOrNothing),
+        )
     }
 
-    fn buy(&mut self, account_ids: Vec<AccountId>, percentage: u32) {
-        Self::require_role(&Role::Executor);
-        Self::require_unpaused();
+    fn buy(
+        &mut self,
+        account_ids: Vec<AccountId>,
+        percentage: u32,
+        atomic: bool,
+        max_spend: Option<U128>,
+    ) -> Vec<AccountId> {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Executor));
+        Self::panic_on_grant_error(self.try_require_operation_unpaused(Operation::Buy));
 
         if percentage == 0 {
             self.decline_orders(account_ids);
-            return;
+            return Vec::new();
         }
 
-        for account_id in account_ids {
-            let pending_issue_ats: HashSet<u32> = self
-                .pending_transfers
-                .get(&account_id)
-                .map(|transfers| transfers.iter().map(|(date, _)| *date).collect())
-                .unwrap_or_default();
+        let estimator = PessimisticGasEstimator::new(GAS_PER_BUY_ACCOUNT);
+        if atomic {
+            estimator.require_affordable(account_ids.len() as u64, || "Buy batch (atomic).");
+        }
+
+        let total = account_ids.len();
+        let attempted_accounts = account_ids.clone();
+        let mut skipped = Vec::new();
+        let mut journal = Journal::default();
+        let mut unprocessed = Vec::new();
+        // Orders eligible to fill this batch, collected without mutating anything yet so they can
+        // be sorted cheapest-first (ascending `min_buy_bps`, no reserve sorting first) before
+        // `max_spend` is applied.
+        let mut candidates: Vec<(AccountId, u32, u128, u32)> = Vec::new();
+        // Guards against `account_ids` listing the same account more than once: without this, a
+        // repeated account would have its grants re-collected into `candidates` a second time
+        // before any mutation has happened yet (the `grant.locked` check below doesn't catch it,
+        // since `buy` never locks anything), double-counting the buyback and, in `atomic` mode,
+        // journaling the same grant's pre-batch snapshot twice.
+        let mut seen = HashSet::new();
+
+        let mut accounts = account_ids.into_iter().enumerate();
+        while let Some((index, account_id)) = accounts.next() {
+            if !atomic && !estimator.can_afford((total - index) as u64) {
+                unprocessed.push(account_id);
+                unprocessed.extend(accounts.map(|(_, account_id)| account_id));
+                break;
+            }
 
             if let Some(account) = self.accounts.get_mut(&account_id) {
                 for (issue_at, grant) in account.grants.iter_mut() {
-                    if pending_issue_ats.contains(issue_at) {
+                    if grant.locked || !grant.conditions.is_empty() {
+                        continue;
+                    }
+
+                    if !seen.insert(TransferKey {
+                        account_id: account_id.clone(),
+                        issue_at: *issue_at,
+                    }) {
                         continue;
                     }
 
@@ -305,24 +855,175 @@ impl GrantApi for Contract {
                         continue;
                     }
 
-                    let bought_amount = (order_amount * percentage as u128) / 10_000;
-                    grant.claimed_amount = U128::from(grant.claimed_amount.0 + bought_amount);
-                    grant.order_amount = U128::from(0);
-                    self.spare_balance.0 += bought_amount;
+                    let min_buy_bps = grant.min_buy_bps.unwrap_or(0);
+                    if min_buy_bps > percentage {
+                        log_str(&format!(
+                            "Skipped order for {} at issue date {}: reserve {} bps above fill {} bps",
+                            account_id, issue_at, min_buy_bps, percentage
+                        ));
+                        skipped.push((account_id.clone(), *issue_at, min_buy_bps));
+                        continue;
+                    }
+
+                    if atomic {
+                        journal.checkpoint(
+                            TransferKey {
+                                account_id: account_id.clone(),
+                                issue_at: *issue_at,
+                            },
+                            grant,
+                        );
+                    }
+
+                    candidates.push((account_id.clone(), *issue_at, order_amount, min_buy_bps));
+                }
+            }
+        }
+
+        if atomic && !skipped.is_empty() {
+            journal.revert(self);
+            for account_id in &attempted_accounts {
+                self.last_error.insert(
+                    account_id.clone(),
+                    GrantError::InsufficientOrder.to_string(),
+                );
+            }
+            self.commit_event(&LtipEvent::BuybackSkipped(skipped));
+            return Vec::new();
+        }
+
+        candidates.sort_by_key(|(_, _, _, min_buy_bps)| *min_buy_bps);
+
+        if atomic {
+            if let Some(max_spend) = max_spend {
+                let total_cost: u128 = candidates
+                    .iter()
+                    .map(|(_, _, order_amount, _)| (order_amount * percentage as u128) / 10_000)
+                    .sum();
+                require!(
+                    total_cost <= max_spend.0,
+                    "max_spend is not enough to fill every eligible order atomically"
+                );
+            }
+        }
+
+        let mut filled = Vec::new();
+        let mut spare_balance_delta: u128 = 0;
+        let mut remaining_budget = max_spend.map(|max_spend| max_spend.0);
+        let mut budget_exhausted_accounts = HashSet::new();
+        let buyback_fee_bps = self
+            .fee_schedule
+            .as_ref()
+            .map(|fee_schedule| fee_schedule.buyback_bps)
+            .unwrap_or(0);
+        let mut batch_fee_total: u128 = 0;
+
+        for (account_id, issue_at, order_amount, _) in candidates {
+            let bought_amount = (order_amount * percentage as u128) / 10_000;
+
+            let actual_amount = match remaining_budget.as_mut() {
+                Some(budget) => {
+                    if *budget == 0 {
+                        budget_exhausted_accounts.insert(account_id);
+                        continue;
+                    }
+                    let actual_amount = cmp::min(bought_amount, *budget);
+                    *budget -= actual_amount;
+                    actual_amount
                 }
+                None => bought_amount,
+            };
+
+            let fee_amount = (actual_amount * buyback_fee_bps as u128) / 10_000;
+
+            if let Some(grant) = self
+                .accounts
+                .get_mut(&account_id)
+                .and_then(|account| account.grants.get_mut(&issue_at))
+            {
+                grant.claimed_amount.0 += actual_amount - fee_amount;
+                grant.order_amount.0 -= actual_amount;
+            }
+
+            batch_fee_total += fee_amount;
+            spare_balance_delta += actual_amount;
+            filled.push((account_id, issue_at, actual_amount));
+        }
+        journal.commit();
+        self.accrued_fees.0 += batch_fee_total;
+
+        for account_id in budget_exhausted_accounts {
+            unprocessed.push(account_id);
+        }
+
+        self.spare_balance.0 += spare_balance_delta;
+
+        let unprocessed_accounts: HashSet<AccountId> = unprocessed.iter().cloned().collect();
+        let filled_accounts: HashSet<AccountId> = filled
+            .iter()
+            .map(|(account_id, _, _)| account_id.clone())
+            .collect();
+        for account_id in &attempted_accounts {
+            if unprocessed_accounts.contains(account_id) {
+                continue;
+            }
+
+            if filled_accounts.contains(account_id) {
+                self.last_error.remove(account_id);
+            } else {
+                self.last_error.insert(
+                    account_id.clone(),
+                    GrantError::InsufficientOrder.to_string(),
+                );
             }
         }
+
+        if !filled.is_empty() {
+            self.commit_event(&LtipEvent::BuybackFilled(filled));
+        }
+        if !skipped.is_empty() {
+            self.commit_event(&LtipEvent::BuybackSkipped(skipped));
+        }
+
+        unprocessed
     }
 
-    fn get_orders(&self) -> Vec<(AccountId, u32, U128)> {
+    fn get_orders(&self, filter: OrderFilter) -> Vec<Order> {
         let mut orders = Vec::new();
+
         for (account_id, account) in self.accounts.iter() {
+            if let Some(accounts) = &filter.accounts {
+                if !accounts.contains(account_id) {
+                    continue;
+                }
+            }
+
             for (issue_at, grant) in account.grants.iter() {
-                if grant.order_amount.0 > 0 {
-                    orders.push((account_id.clone(), *issue_at, grant.order_amount));
+                let amount = grant.order_amount;
+                if amount.0 == 0 {
+                    continue;
+                }
+
+                if let Some(min_amount) = filter.min_amount {
+                    if amount.0 < min_amount.0 {
+                        continue;
+                    }
+                }
+
+                if filter.payable_only && amount.0 > self.spare_balance.0 {
+                    continue;
                 }
+
+                orders.push(Order {
+                    account_id: account_id.clone(),
+                    issue_at: *issue_at,
+                    amount,
+                    vested_amount: grant.get_vested_amount().into(),
+                    reserve_bps: grant.min_buy_bps,
+                });
             }
         }
+
         orders
     }
 
@@ -332,10 +1033,15 @@ impl GrantApi for Contract {
                 .grants
                 .iter()
                 .map(|(issue_at, grant)| {
-                    let cliff_end_at = *issue_at + self.config.cliff_duration;
-                    let vesting_end_at = cliff_end_at + self.config.vesting_duration;
+                    let (cliff_end_at, vesting_end_at) = match &grant.schedule {
+                        Schedule::Linear {
+                            cliff_end,
+                            vesting_end,
+                        } => (Some(*cliff_end), Some(*vesting_end)),
+                        Schedule::Milestone { .. } => (None, None),
+                    };
 
-                    let vested_amount = grant.get_vested_amount(*issue_at, &self.config);
+                    let vested_amount = grant.get_vested_amount();
 
                     GrantView {
                         issued_at: *issue_at,
@@ -348,6 +1054,7 @@ impl GrantApi for Contract {
                         not_vested_amount: (grant.total_amount.0 - vested_amount).into(),
                         claimable_amount: (vested_amount - grant.claimed_amount.0).into(),
                         terminated_at: grant.terminated_at,
+                        conditions: grant.conditions.clone(),
                     }
                 })
                 .collect();
@@ -361,143 +1068,730 @@ impl GrantApi for Contract {
         None
     }
 
+    fn get_grant_schedule(&self, account_id: &AccountId, issue_at: u32) -> Option<(u32, u32)> {
+        let grant = self.accounts.get(account_id)?.grants.get(&issue_at)?;
+
+        match &grant.schedule {
+            Schedule::Linear {
+                cliff_end,
+                vesting_end,
+            } => Some((*cliff_end, *vesting_end)),
+            Schedule::Milestone { .. } => None,
+        }
+    }
+
+    fn update_lockup(&mut self, account_id: AccountId, issue_at: u32, new_schedule: Schedule) {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Custodian));
+
+        let grant = self
+            .accounts
+            .get_mut(&account_id)
+            .and_then(|account| account.grants.get_mut(&issue_at))
+            .unwrap_or_else(|| {
+                panic_str("No grant found for this account at the given issue date")
+            });
+
+        let Schedule::Linear {
+            cliff_end,
+            vesting_end,
+        } = &grant.schedule
+        else {
+            panic_str("Only a Schedule::Linear lockup can be extended by a custodian");
+        };
+
+        let Schedule::Linear {
+            cliff_end: new_cliff_end,
+            vesting_end: new_vesting_end,
+        } = &new_schedule
+        else {
+            panic_str("new_schedule must be a Schedule::Linear lockup");
+        };
+
+        require!(
+            new_cliff_end >= cliff_end && new_vesting_end >= vesting_end,
+            "A custodian may only extend a lockup, never shorten it"
+        );
+
+        grant.schedule = new_schedule;
+    }
+
     fn get_spare_balance(&self) -> U128 {
         self.spare_balance
     }
 
-    fn get_pending_transfers(&self) -> HashMap<AccountId, Vec<(u32, U128)>> {
-        self.pending_transfers.clone()
+    fn get_forfeited_total(&self) -> U128 {
+        self.total_forfeited
     }
 
-    fn terminate(&mut self, account_id: AccountId, timestamp: u32) {
-        Self::require_role(&Role::Executor);
-        Self::require_unpaused();
+    fn get_pending_transfers(&self) -> HashMap<AccountId, Vec<PendingTransfer>> {
+        let mut pending = HashMap::new();
 
-        if let Some(account) = self.accounts.get_mut(&account_id) {
-            for (issue_at, grant) in account.grants.iter_mut() {
-                let unvested_amount = grant.terminate(*issue_at, &self.config, timestamp);
+        for (account_id, account) in self.accounts.iter() {
+            let locked_grants: Vec<PendingTransfer> = account
+                .grants
+                .iter()
+                .filter(|(_, grant)| grant.locked)
+                .map(|(issue_at, grant)| PendingTransfer {
+                    issue_at: *issue_at,
+                    amount: grant.pending_amount,
+                    authorized_bps: grant.locked_bps.unwrap_or(0),
+                })
+                .collect();
 
-                self.spare_balance.0 += unvested_amount;
+            if !locked_grants.is_empty() {
+                pending.insert(account_id.clone(), locked_grants);
             }
         }
-    }
-}
 
-impl Contract {
-    fn decline_orders(&mut self, account_ids: Vec<AccountId>) {
-        for account_id in account_ids {
-            let pending_issue_ats: HashSet<u32> = self
-                .pending_transfers
-                .get(&account_id)
-                .map(|transfers| transfers.iter().map(|(date, _)| *date).collect())
-                .unwrap_or_default();
-
-            if let Some(account) = self.accounts.get_mut(&account_id) {
-                for (issue_at, grant) in account.grants.iter_mut() {
-                    if !pending_issue_ats.contains(issue_at) {
-                        grant.order_amount = U128::from(0);
-                    }
-                }
-
-                log_str(&format!(
-                    "Declined orders for account {} (skipped pending transfers)",
-                    account_id
-                ));
+        if let Some(fee_schedule) = &self.fee_schedule {
+            if self.accrued_fees.0 > 0 {
+                pending
+                    .entry(fee_schedule.fee_collector.clone())
+                    .or_default()
+                    .push(PendingTransfer {
+                        issue_at: 0,
+                        amount: self.accrued_fees,
+                        authorized_bps: fee_schedule.authorize_bps,
+                    });
             }
         }
 
-        self.unpause();
+        pending
     }
 
-    pub(crate) fn create_grant_internal(
-        &mut self,
-        account_id: &AccountId,
-        issue_at: u32,
-        total_amount: U128,
-        claimed_amount: Option<U128>,
-    ) {
-        let account = self.accounts.entry(account_id.clone()).or_insert(Account {
-            grants: HashMap::new(),
-        });
+    fn reconcile_pending_transfer(&mut self, account_id: AccountId, issue_at: u32, batch_id: u64) {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Executor));
 
-        require!(
-            !account.grants.contains_key(&issue_at),
-            "A grant has alredy been issued on this date"
-        );
+        let Some(account) = self.accounts.get_mut(&account_id) else {
+            return;
+        };
+        let Some(grant) = account.grants.get_mut(&issue_at) else {
+            return;
+        };
+
+        if !grant.locked || grant.locked_batch_id != Some(batch_id) {
+            return;
+        }
+
+        let locked_at = grant.locked_at_block_height.unwrap_or(0);
+        if env::block_height() < locked_at + STUCK_TRANSFER_BLOCKS {
+            panic_str("Batch hasn't been stuck long enough to reconcile yet");
+        }
+
+        let pending_amount = grant.pending_amount.0;
+        grant.order_amount.0 += pending_amount;
+        grant.pending_amount = U128::from(0);
+        grant.locked = false;
+        grant.locked_batch_id = None;
+        grant.locked_at_block_height = None;
+        grant.locked_bps = None;
+
+        log_str(&format!(
+            "Reconciled stuck batch {} for {} at issue date {}: restored {} to order_amount",
+            batch_id, account_id, issue_at, pending_amount
+        ));
+
+        self.commit_event(&LtipEvent::TransferReverted(vec![(
+            account_id,
+            issue_at,
+            pending_amount,
+        )]));
+    }
+
+    fn get_failed_transfers(&self) -> HashMap<AccountId, Vec<FailedTransfer>> {
+        let mut failed: HashMap<AccountId, Vec<FailedTransfer>> = HashMap::new();
+
+        for (transfer_key, (amount, authorized_bps)) in self.failed_transfers.iter() {
+            failed
+                .entry(transfer_key.account_id.clone())
+                .or_default()
+                .push(FailedTransfer {
+                    issue_at: transfer_key.issue_at,
+                    amount: *amount,
+                    authorized_bps: *authorized_bps,
+                });
+        }
+
+        failed
+    }
+
+    fn retry_failed(&mut self, account_ids: Vec<AccountId>) {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Executor));
+
+        let accounts: HashSet<AccountId> = account_ids.into_iter().collect();
+
+        let mut by_bps: HashMap<u32, Vec<AccountId>> = HashMap::new();
+        for (transfer_key, (_, authorized_bps)) in self.failed_transfers.iter() {
+            if accounts.contains(&transfer_key.account_id) {
+                by_bps
+                    .entry(*authorized_bps)
+                    .or_default()
+                    .push(transfer_key.account_id.clone());
+            }
+        }
+
+        self.failed_transfers
+            .retain(|transfer_key, _| !accounts.contains(&transfer_key.account_id));
+
+        for (authorized_bps, mut retry_accounts) in by_bps {
+            retry_accounts.sort();
+            retry_accounts.dedup();
+            self.authorize(retry_accounts, Some(authorized_bps), false);
+        }
+    }
+
+    fn terminate(&mut self, account_id: AccountId, timestamp: u32) {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Executor));
+        Self::panic_on_grant_error(self.try_require_operation_unpaused(Operation::Terminate));
+
+        // Reclaim any resting `SellOrder`s against this account's grants before termination, so
+        // the loop below's `grant.terminate` clawback (capped at what's actually still vested)
+        // also sees and forfeits whatever a sell order had carved out of `order_amount`. Done as
+        // a separate pass since `release_sell_orders_for_grant` needs `&mut self` as a whole,
+        // which would conflict with the per-grant borrow the main loop holds.
+        let issue_ats: Vec<u32> = self
+            .accounts
+            .get(&account_id)
+            .map(|account| account.grants.keys().copied().collect())
+            .unwrap_or_default();
+        for issue_at in issue_ats {
+            let reclaimed = self.release_sell_orders_for_grant(&account_id, issue_at);
+            if reclaimed > 0 {
+                if let Some(grant) = self
+                    .accounts
+                    .get_mut(&account_id)
+                    .and_then(|account| account.grants.get_mut(&issue_at))
+                {
+                    grant.order_amount.0 += reclaimed;
+                }
+            }
+        }
+
+        let mut terminated = Vec::new();
+        // Grants just terminated that still hold staked funds are recalled below, grouped by
+        // pool so each pool sees a single `withdraw` call rather than one per grant.
+        let mut unstake_by_pool: HashMap<AccountId, (Vec<TransferKey>, u128)> = HashMap::new();
+
+        if let Some(account) = self.accounts.get_mut(&account_id) {
+            for (issue_at, grant) in account.grants.iter_mut() {
+                if grant.locked {
+                    continue;
+                }
+
+                let was_terminated = grant.terminated_at.is_some();
+                let unvested_amount = grant.terminate(*issue_at, timestamp);
+
+                if !was_terminated && grant.terminated_at.is_some() {
+                    self.spare_balance.0 += unvested_amount;
+                    self.total_forfeited.0 += unvested_amount;
+                    terminated.push((*issue_at, unvested_amount));
+
+                    if grant.staked_amount.0 > 0 && !grant.staking_locked {
+                        if let Some(pool_id) = grant.staking_pool_id.clone() {
+                            grant.staking_locked = true;
+                            grant.pending_stake_amount = grant.staked_amount;
+
+                            let entry = unstake_by_pool.entry(pool_id).or_insert((Vec::new(), 0));
+                            entry.1 += grant.staked_amount.0;
+                            entry.0.push(TransferKey {
+                                account_id: account_id.clone(),
+                                issue_at: *issue_at,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if terminated.is_empty() {
+            self.last_error.insert(
+                account_id.clone(),
+                GrantError::AlreadyTerminated.to_string(),
+            );
+        } else {
+            self.last_error.remove(&account_id);
+            self.commit_event(&LtipEvent::Terminate((account_id, terminated)));
+        }
+
+        for (pool_id, (transfer_keys, total_amount)) in unstake_by_pool {
+            let args =
+                serde_json::to_vec(&serde_json::json!({ "amount": U128::from(total_amount) }))
+                    .unwrap();
+            self.request_stake_call(
+                pool_id,
+                "withdraw",
+                args,
+                0,
+                transfer_keys,
+                "on_unstake_complete",
+            );
+        }
+    }
+
+    fn terminate_vesting(&mut self, account_id: AccountId, issue_at: u32) {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Executor));
+        Self::panic_on_grant_error(self.try_require_operation_unpaused(Operation::Terminate));
+
+        // Same reclaim-before-clawback as `terminate`: restore any resting `SellOrder`'s amount
+        // into `order_amount` first so it isn't left tradeable past termination.
+        let reclaimed = self.release_sell_orders_for_grant(&account_id, issue_at);
+
+        let Some(grant) = self
+            .accounts
+            .get_mut(&account_id)
+            .and_then(|account| account.grants.get_mut(&issue_at))
+        else {
+            self.last_error
+                .insert(account_id, GrantError::AlreadyTerminated.to_string());
+            return;
+        };
+
+        grant.order_amount.0 += reclaimed;
+
+        let was_terminated = grant.terminated_at.is_some();
+        let unvested_amount = if grant.locked {
+            0
+        } else {
+            grant.terminate(issue_at, now())
+        };
+
+        if was_terminated || grant.locked || grant.terminated_at.is_none() {
+            self.last_error
+                .insert(account_id, GrantError::AlreadyTerminated.to_string());
+            return;
+        }
+
+        self.spare_balance.0 += unvested_amount;
+        self.total_forfeited.0 += unvested_amount;
+        self.last_error.remove(&account_id);
+        self.commit_event(&LtipEvent::Terminate((
+            account_id.clone(),
+            vec![(issue_at, unvested_amount)],
+        )));
+
+        if grant.staked_amount.0 > 0 && !grant.staking_locked {
+            if let Some(pool_id) = grant.staking_pool_id.clone() {
+                grant.staking_locked = true;
+                grant.pending_stake_amount = grant.staked_amount;
+                let staked_amount = grant.staked_amount.0;
+
+                self.request_stake_call(
+                    pool_id,
+                    "withdraw",
+                    serde_json::to_vec(&serde_json::json!({ "amount": U128::from(staked_amount) }))
+                        .unwrap(),
+                    0,
+                    vec![TransferKey {
+                        account_id,
+                        issue_at,
+                    }],
+                    "on_unstake_complete",
+                );
+            }
+        }
+    }
+
+    fn confirm_tranche(&mut self, account_id: AccountId, issue_at: u32, tranche_index: u32) {
+        Self::panic_on_grant_error(Self::try_require_unpaused());
+
+        let account = self
+            .accounts
+            .get_mut(&account_id)
+            .unwrap_or_else(|| panic_str("Account not found"));
+        let grant = account
+            .grants
+            .get_mut(&issue_at)
+            .unwrap_or_else(|| panic_str("Grant not found"));
+
+        let Schedule::Milestone { tranches } = &mut grant.schedule else {
+            panic_str("Grant does not use a milestone schedule");
+        };
+
+        let tranche = tranches
+            .get_mut(tranche_index as usize)
+            .unwrap_or_else(|| panic_str("Tranche not found"));
+
+        if let Some(approver) = &tranche.approver {
+            require!(
+                &env::predecessor_account_id() == approver,
+                "Only the designated approver may confirm this tranche"
+            );
+        }
+
+        tranche.released = true;
+
+        self.commit_event(&LtipEvent::TrancheConfirmed((
+            account_id,
+            issue_at,
+            tranche_index,
+        )));
+    }
+
+    fn apply_witness(&mut self, account_id: AccountId, issue_at: u32) {
+        let caller = env::predecessor_account_id();
+        let now = env::block_timestamp() / 1_000_000_000;
+
+        let grant = self
+            .accounts
+            .get_mut(&account_id)
+            .and_then(|account| account.grants.get_mut(&issue_at))
+            .unwrap_or_else(|| panic_str("Grant not found"));
+
+        grant.conditions.retain(|condition| match condition {
+            Condition::Timestamp(seconds) => now < u64::from(*seconds),
+            Condition::Oracle { reporter } => reporter != &caller,
+        });
+    }
+
+    fn get_last_error(&self, account_id: AccountId) -> Option<String> {
+        self.last_error.get(&account_id).cloned()
+    }
+
+    fn prune(&mut self, account_ids: Vec<AccountId>, dust_threshold: U128) {
+        Self::panic_on_grant_error(self.try_require_role_active(&Role::Executor));
+
+        let owner_id = self
+            .own_get_owner()
+            .unwrap_or_else(|| panic_str("Contract has no owner to refund storage to"));
+
+        let storage_usage_before = env::storage_usage();
+        let mut pruned_accounts = Vec::new();
+
+        for account_id in account_ids {
+            let grants_empty = {
+                let Some(account) = self.accounts.get_mut(&account_id) else {
+                    continue;
+                };
+
+                account.grants.retain(|_, grant| {
+                    let settled = !grant.locked
+                        && grant.order_amount.0 == 0
+                        && grant.total_amount.0 - grant.claimed_amount.0 <= dust_threshold.0;
+                    !settled
+                });
+
+                account.grants.is_empty()
+            };
+
+            if grants_empty {
+                self.accounts.remove(&account_id);
+                self.storage_deposits.remove(&account_id);
+                pruned_accounts.push(account_id);
+            }
+        }
+
+        let bytes_freed = storage_usage_before.saturating_sub(env::storage_usage());
+        if bytes_freed > 0 {
+            let refund = NearToken::from_yoctonear(
+                u128::from(bytes_freed) * STORAGE_PRICE_PER_BYTE.as_yoctonear(),
+            );
+            Promise::new(owner_id).transfer(refund);
+        }
+
+        if !pruned_accounts.is_empty() {
+            log_str(&format!("Pruned {} empty accounts", pruned_accounts.len()));
+        }
+    }
+}
+
+impl Contract {
+    /// Non-panicking counterpart to `Contract::require_role_active`, used by `GrantApi`'s
+    /// public entry points so a missing or expired role surfaces as `GrantError::Unauthorized`
+    /// instead of an untyped `require!`/`panic_str`.
+    fn try_require_role_active(&self, role: &Role) -> Result<(), GrantError> {
+        let caller = env::predecessor_account_id();
+        if self.has_role(&caller, role.clone()) {
+            Ok(())
+        } else {
+            Err(GrantError::Unauthorized)
+        }
+    }
+
+    /// Non-panicking counterpart to `Self::require_unpaused`, for entry points (`claim`,
+    /// `authorize`) that only gate on the contract-wide switch and don't have a matching
+    /// `Operation` to individually freeze.
+    fn try_require_unpaused() -> Result<(), GrantError> {
+        if Self::is_paused() {
+            Err(GrantError::ContractPaused)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Non-panicking counterpart to `Contract::require_operation_unpaused`.
+    fn try_require_operation_unpaused(&self, operation: Operation) -> Result<(), GrantError> {
+        if Self::is_paused() || *self.paused_operations.get(&operation).unwrap_or(&false) {
+            Err(GrantError::ContractPaused)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Converts a `GrantError` into the typed panic `GrantApi`'s public methods raise at the
+    /// `#[near]` boundary. `Result` only ever travels internally between the `try_require_*`
+    /// checks above and this call; every entry point still panics on rejection; it just panics
+    /// with `GrantError`'s own message instead of an ad-hoc string duplicated at each call site.
+    fn panic_on_grant_error(result: Result<(), GrantError>) {
+        if let Err(error) = result {
+            panic_str(&error.to_string());
+        }
+    }
+
+    fn decline_orders(&mut self, account_ids: Vec<AccountId>) {
+        for account_id in account_ids {
+            if let Some(account) = self.accounts.get_mut(&account_id) {
+                for (_, grant) in account.grants.iter_mut() {
+                    if !grant.locked {
+                        grant.order_amount = U128::from(0);
+                    }
+                }
+
+                log_str(&format!(
+                    "Declined orders for account {} (skipped locked grants)",
+                    account_id
+                ));
+            }
+        }
+    }
+
+    pub(crate) fn create_grant_internal(
+        &mut self,
+        account_id: &AccountId,
+        issue_at: u32,
+        total_amount: U128,
+        claimed_amount: Option<U128>,
+        schedule: Option<Schedule>,
+    ) {
+        self.require_operation_unpaused(Operation::CreateGrant);
+
+        let account = self.accounts.entry(account_id.clone()).or_insert(Account {
+            grants: HashMap::new(),
+        });
+
+        require!(
+            !account.grants.contains_key(&issue_at),
+            "A grant has alredy been issued on this date"
+        );
+
+        let schedule = schedule.unwrap_or_else(|| Schedule::Linear {
+            cliff_end: self.config.cliff_end(issue_at),
+            vesting_end: self.config.vesting_end(issue_at),
+        });
+
+        if let Schedule::Milestone { tranches } = &schedule {
+            let tranche_total: u128 = tranches.iter().map(|tranche| tranche.amount.0).sum();
+            require!(
+                tranche_total == total_amount.0,
+                "Milestone tranches must sum to the grant's total_amount"
+            );
+        }
 
         let grant = Grant {
             total_amount,
             claimed_amount: claimed_amount.unwrap_or_else(|| U128::from(0)),
             order_amount: U128::from(0),
             terminated_at: None,
+            schedule,
+            pending_amount: U128::from(0),
+            locked: false,
+            locked_bps: None,
+            min_buy_bps: None,
+            locked_batch_id: None,
+            locked_at_block_height: None,
+            staked_amount: U128::from(0),
+            pending_stake_amount: U128::from(0),
+            staking_locked: false,
+            staking_pool_id: None,
+            conditions: Vec::new(),
         };
 
         account.grants.insert(issue_at, grant);
     }
 
-    pub(crate) fn issue_internal(&mut self, issue_at: u32, grants: Vec<(AccountId, U128)>) {
+    pub(crate) fn issue_internal(
+        &mut self,
+        issue_at: u32,
+        grants: Vec<(AccountId, U128, Option<Schedule>)>,
+        fill_policy: FillPolicy,
+    ) -> Vec<IssueReport> {
         Self::require_unpaused();
 
-        let total_amount: u128 = grants.iter().map(|(_, amount)| amount.0).sum();
-        if total_amount > self.spare_balance.0 {
-            env::panic_str(&format!(
-                "Insufficient spare balance: required {}, available {}",
-                total_amount, self.spare_balance.0
-            ));
+        for (account_id, amount, _) in &grants {
+            assert_nonzero_amount(amount.0, || {
+                format!("Grant for {account_id} at issue date {issue_at}")
+            });
+        }
+
+        let total_amount: u128 = grants.iter().map(|(_, amount, _)| amount.0).sum();
+        let available = self.spare_balance.0;
+
+        let planned: Vec<(AccountId, U128, Option<Schedule>, u128)> = if total_amount <= available {
+            grants
+                .into_iter()
+                .map(|(account_id, amount, schedule)| {
+                    let issued = amount.0;
+                    (account_id, amount, schedule, issued)
+                })
+                .collect()
+        } else {
+            match fill_policy {
+                FillPolicy::AllOrNothing => {
+                    env::panic_str(&format!(
+                        "Insufficient spare balance: required {}, available {}",
+                        total_amount, available
+                    ));
+                }
+                FillPolicy::PriorityOrder => {
+                    let mut remaining = available;
+                    grants
+                        .into_iter()
+                        .map(|(account_id, amount, schedule)| {
+                            let issued = if amount.0 <= remaining {
+                                remaining -= amount.0;
+                                amount.0
+                            } else {
+                                0
+                            };
+                            (account_id, amount, schedule, issued)
+                        })
+                        .collect()
+                }
+                FillPolicy::ProRata => {
+                    let mut issued_amounts: Vec<u128> = grants
+                        .iter()
+                        .map(|(_, amount, _)| (amount.0 * available) / total_amount)
+                        .collect();
+
+                    let mut remainder = available - issued_amounts.iter().sum::<u128>();
+                    let mut by_account: Vec<usize> = (0..grants.len()).collect();
+                    by_account.sort_by(|&a, &b| grants[a].0.cmp(&grants[b].0));
+
+                    for index in by_account {
+                        if remainder == 0 {
+                            break;
+                        }
+                        issued_amounts[index] += 1;
+                        remainder -= 1;
+                    }
+
+                    grants
+                        .into_iter()
+                        .zip(issued_amounts)
+                        .map(|((account_id, amount, schedule), issued)| {
+                            (account_id, amount, schedule, issued)
+                        })
+                        .collect()
+                }
+            }
+        };
+
+        let event_grants: Vec<(AccountId, u128)> = planned
+            .iter()
+            .filter(|(_, _, _, issued)| *issued > 0)
+            .map(|(account_id, _, _, issued)| (account_id.clone(), *issued))
+            .collect();
+
+        let mut reports = Vec::with_capacity(planned.len());
+        let mut deposit_remaining = env::attached_deposit();
+        let mut issued_total: u128 = 0;
+
+        for (account_id, requested_amount, schedule, issued_amount) in planned {
+            if issued_amount > 0 {
+                let storage_usage_before = env::storage_usage();
+                self.create_grant_internal(
+                    &account_id,
+                    issue_at,
+                    U128::from(issued_amount),
+                    None,
+                    schedule,
+                );
+                deposit_remaining =
+                    match self.charge_storage(&account_id, storage_usage_before, deposit_remaining)
+                    {
+                        Ok(remaining) => remaining,
+                        Err(message) => {
+                            // The grant was only just inserted above to measure its storage cost;
+                            // undo that insertion before panicking so no half-charged grant survives.
+                            // An account entry left with no grants by that removal is dropped too.
+                            let account_now_empty = match self.accounts.get_mut(&account_id) {
+                                Some(account) => {
+                                    account.grants.remove(&issue_at);
+                                    account.grants.is_empty()
+                                }
+                                None => false,
+                            };
+                            if account_now_empty {
+                                self.accounts.remove(&account_id);
+                            }
+                            panic_str(&message);
+                        }
+                    };
+                issued_total += issued_amount;
+            }
+
+            reports.push(IssueReport {
+                account_id,
+                requested_amount,
+                issued_amount: U128::from(issued_amount),
+            });
         }
 
-        for (account_id, amount) in grants {
-            self.create_grant_internal(&account_id, issue_at, amount, None);
+        if deposit_remaining.as_yoctonear() > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(deposit_remaining);
         }
 
-        self.spare_balance = U128::from(self.spare_balance.0 - total_amount);
+        self.spare_balance = U128::from(self.spare_balance.0 - issued_total);
 
         log_str(&format!(
             "Issued grants with total amount {} at timestamp {}",
-            total_amount, issue_at
+            issued_total, issue_at
         ));
-    }
-}
 
-#[cfg(test)]
-impl Contract {
-    pub fn clear_pending_transfers(&mut self) {
-        self.pending_transfers.clear();
+        self.commit_event(&LtipEvent::Issue((issue_at, event_grants)));
+
+        reports
     }
 }
 
 impl Grant {
-    pub(crate) fn get_vested_amount(&self, issue_at: u32, config: &Config) -> u128 {
+    /// Computes how much of `total_amount` has unlocked as of now. For `Schedule::Linear`, this
+    /// is the lockup-contract-style cliff-then-linear curve: `0` before `cliff_end`,
+    /// `total_amount` at or after `vesting_end`, and `total_amount * (now - cliff_end) /
+    /// (vesting_end - cliff_end)` in between (via `calculate_vested_amount`'s overflow-safe
+    /// 256-bit math). `claim()` then places `vested_amount - claimed_amount` into
+    /// `order_amount`, so a grant releases continuously rather than as a single unlock.
+    pub(crate) fn get_vested_amount(&self) -> u128 {
         let now = now();
 
-        let cliff_end = issue_at + config.cliff_duration;
-        let effective_vesting_duration = self
-            .terminated_at
-            .map_or(config.vesting_duration, |t| t.saturating_sub(cliff_end));
+        match &self.schedule {
+            Schedule::Linear {
+                cliff_end,
+                vesting_end,
+            } => {
+                let effective_vesting_end = self.terminated_at.unwrap_or(*vesting_end);
 
-        calculate_vested_amount(
-            now,
-            cliff_end,
-            cliff_end + effective_vesting_duration,
-            self.total_amount.0,
-        )
+                calculate_vested_amount(now, *cliff_end, effective_vesting_end, self.total_amount.0)
+            }
+            Schedule::Milestone { tranches } => tranches
+                .iter()
+                .filter(|tranche| tranche.released && now >= tranche.unlock_after)
+                .map(|tranche| tranche.amount.0)
+                .sum(),
+        }
     }
 
-    pub(crate) fn terminate(&mut self, issue_at: u32, config: &Config, terminate_at: u32) -> u128 {
+    pub(crate) fn terminate(&mut self, issue_at: u32, terminate_at: u32) -> u128 {
         if self.terminated_at.is_some() {
             return 0;
         }
 
-        let cliff_end = config.cliff_end(issue_at);
-        let vesting_end = config.vesting_end(issue_at);
-
-        if terminate_at > vesting_end {
-            return 0;
+        if let Schedule::Linear { vesting_end, .. } = &self.schedule {
+            if terminate_at > *vesting_end {
+                return 0;
+            }
         }
 
-        let now = now();
-        let vested_amount =
-            calculate_vested_amount(now, cliff_end, vesting_end, self.total_amount.0);
+        let vested_amount = self.get_vested_amount();
 
         if vested_amount >= self.claimed_amount.0 {
             self.terminated_at = terminate_at.into();
@@ -510,17 +1804,29 @@ impl Grant {
             return unvested_amount;
         }
 
-        let effective_vesting_duration =
-            u32::try_from(self.claimed_amount.0 / self.performance(config.vesting_duration))
+        // Claimed more than what's currently vested (e.g. a buyback against the projected
+        // curve). Only the continuous linear schedule supports backdating the effective
+        // termination point to where `claimed_amount` would have been reached.
+        self.terminated_at = match &self.schedule {
+            Schedule::Linear {
+                cliff_end,
+                vesting_end,
+            } => {
+                let effective_vesting_duration = u32::try_from(
+                    self.claimed_amount.0 / self.performance(*vesting_end - *cliff_end),
+                )
                 .unwrap_or_else(|_| panic_str("Failed to evaluate effective vesting duration"));
 
-        self.terminated_at = (issue_at + config.cliff_duration + effective_vesting_duration).into();
+                (*cliff_end + effective_vesting_duration).into()
+            }
+            Schedule::Milestone { .. } => terminate_at.into(),
+        };
         self.order_amount.0 = 0;
 
         let unvested_amount = self.total_amount.0 - self.claimed_amount.0;
         self.total_amount = self.claimed_amount;
 
-        return unvested_amount;
+        unvested_amount
     }
 
     /// Amount of tokens being vested per second
@@ -543,17 +1849,23 @@ impl Config {
 mod tests {
     use std::panic::{self, AssertUnwindSafe};
 
-    use near_sdk::{json_types::U128, test_utils::accounts, AccountId, Gas, PromiseResult};
-    use near_sdk_contract_tools::pause::Pause;
+    use near_sdk::{
+        json_types::U128, test_utils::accounts, AccountId, Gas, NearToken, PromiseResult,
+    };
+    use near_sdk_contract_tools::{ft::nep141::GAS_FOR_FT_TRANSFER_CALL, pause::Pause, rbac::Rbac};
     use rstest::*;
 
     use crate::{
         common::{ToOtto, ONE_DAY_IN_SECONDS, ONE_YEAR_IN_SECONDS},
-        grant::{GrantApi, TransferKey},
+        event::AuditApi,
+        grant::{
+            Condition, FillPolicy, GrantApi, GrantError, OrderFilter, Schedule, Tranche,
+            TransferKey,
+        },
         testing_api::DEFAULT_CLIFF,
         tests::context::TestContext,
         tests::fixtures::*,
-        Contract,
+        Contract, Role,
     };
 
     #[rstest]
@@ -562,7 +1874,7 @@ mod tests {
         mut contract: Contract,
         alice: AccountId,
     ) {
-        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
 
         context.switch_account(&alice);
         context.set_block_timestamp_in_seconds(1_500);
@@ -580,7 +1892,7 @@ mod tests {
         mut contract: Contract,
         alice: AccountId,
     ) {
-        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
 
         context.switch_account(&alice);
         context.set_block_timestamp_in_seconds(4_000);
@@ -593,115 +1905,173 @@ mod tests {
     }
 
     #[rstest]
-    fn buy_updates_claimed_and_spare_balance(
+    fn claim_with_nothing_vested_records_last_error(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
     ) {
-        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None);
-
-        let initial_spare = contract.spare_balance.0;
-
-        {
-            let account = contract.accounts.get_mut(&alice).unwrap();
-            let grant = account.grants.get_mut(&1_000).unwrap();
-            grant.order_amount = U128::from(5_000);
-        }
-
-        context.switch_to_executor();
-        contract.buy(vec![alice.clone()], 5_000);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&1_000).unwrap();
-        assert_eq!(grant.claimed_amount.0, 2_500);
-        assert_eq!(grant.order_amount.0, 0);
-        assert_eq!(contract.spare_balance.0, initial_spare + 2_500);
-    }
+        context.switch_account(&alice);
+        context.set_block_timestamp_in_seconds(1_500);
 
-    #[rstest]
-    fn issue_reduces_spare_balance(mut context: TestContext, mut contract: Contract) {
-        contract.spare_balance = 10_000.into();
+        contract.claim();
 
-        context.switch_to_issuer();
-        contract.issue(
-            1_000,
-            vec![
-                (accounts(1), U128::from(3_000)),
-                (accounts(2), U128::from(2_000)),
-            ],
+        assert_eq!(
+            contract.get_last_error(alice.clone()),
+            Some(GrantError::NothingToClaim.to_string())
         );
 
-        assert_eq!(contract.spare_balance.0, 5_000);
-        assert!(contract.accounts.get(&accounts(1)).is_some());
+        context.set_block_timestamp_in_seconds(4_000);
+        contract.claim();
+
+        assert_eq!(contract.get_last_error(alice), None);
     }
 
     #[rstest]
-    fn issue_requires_issuer_role(
+    fn claim_with_nothing_newly_vested_since_last_claim_records_last_error(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
-        bob: AccountId,
     ) {
-        contract.spare_balance = 10_000.into();
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
 
         context.switch_account(&alice);
-        let result = panic::catch_unwind(AssertUnwindSafe(|| {
-            contract.issue(1_000, vec![(bob.clone(), 1_000.into())]);
-        }));
+        context.set_block_timestamp_in_seconds(4_000);
+        contract.claim();
 
-        assert!(result.is_err());
-        assert!(contract.accounts.get(&bob).is_none());
-        assert_eq!(contract.spare_balance.0, 10_000);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.claimed_amount = grant.order_amount;
+        }
+
+        let (sequence_before, _) = contract.get_audit_head();
+        contract.claim();
+        let (sequence_after, _) = contract.get_audit_head();
+
+        assert_eq!(
+            sequence_before, sequence_after,
+            "no new event for a claim with nothing newly vested"
+        );
+        assert_eq!(
+            contract.get_last_error(alice),
+            Some(GrantError::NothingToClaim.to_string())
+        );
     }
 
     #[rstest]
-    fn authorize_moves_order_into_pending_transfers(
+    fn buy_updates_claimed_and_spare_balance(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
     ) {
-        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        let initial_spare = contract.spare_balance.0;
 
         {
             let account = contract.accounts.get_mut(&alice).unwrap();
             let grant = account.grants.get_mut(&1_000).unwrap();
-            grant.order_amount = U128::from(4_000);
+            grant.order_amount = U128::from(5_000);
         }
 
         context.switch_to_executor();
-        contract.authorize(vec![alice.clone()], Some(5_000));
-
-        let pending = contract.get_pending_transfers();
-        assert!(pending.contains_key(&alice));
-        let transfers = pending.get(&alice).unwrap();
-        assert_eq!(transfers[0].1 .0, 2_000);
-    }
+        contract.buy(vec![alice.clone()], 5_000, false, None);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.claimed_amount.0, 2_500);
+        assert_eq!(grant.order_amount.0, 0);
+        assert_eq!(contract.spare_balance.0, initial_spare + 2_500);
+    }
 
     #[rstest]
-    fn authorize_requires_executor_role(
+    fn buy_does_not_double_count_an_account_listed_twice(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
     ) {
-        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
 
-        context.switch_account(&alice);
-        let result = panic::catch_unwind(AssertUnwindSafe(|| {
-            contract.authorize(vec![alice.clone()], Some(10_000));
+        let initial_spare = contract.spare_balance.0;
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        context.switch_to_executor();
+        contract.buy(vec![alice.clone(), alice.clone()], 5_000, false, None);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.claimed_amount.0, 2_500);
+        assert_eq!(grant.order_amount.0, 0);
+        assert_eq!(contract.spare_balance.0, initial_spare + 2_500);
+    }
+
+    #[rstest]
+    fn buy_with_fee_schedule_credits_collector_instead_of_grantee(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        use crate::{config::ConfigApi, FeeSchedule};
+
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        context.switch_account(&owner);
+        contract.set_fee_schedule(Some(FeeSchedule {
+            buyback_bps: 1_000,
+            authorize_bps: 0,
+            fee_collector: bob.clone(),
         }));
 
-        assert!(result.is_err());
-        assert!(contract.get_pending_transfers().is_empty());
+        let initial_spare = contract.spare_balance.0;
+
+        context.switch_to_executor();
+        contract.buy(vec![alice.clone()], 5_000, false, None);
+
+        // bought_amount = 5_000 * 50% = 2_500; fee is 10% of that, floored.
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(grant.claimed_amount.0, 2_250);
+        assert_eq!(grant.order_amount.0, 0);
+        assert_eq!(contract.accrued_fees.0, 250);
+        // buy never creates a Promise, so the fee just accrues; spare_balance still tracks the
+        // full bought-back amount regardless of how it's split between grantee and collector.
+        assert_eq!(contract.spare_balance.0, initial_spare + 2_500);
     }
 
     #[rstest]
-    #[should_panic(expected = "Not enough gas left.")]
-    fn authorize_fails_with_insufficient_gas(
+    fn buy_skips_orders_below_reserve_price(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
+        bob: AccountId,
     ) {
-        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
 
         {
             let grant = contract
@@ -709,25 +2079,58 @@ mod tests {
                 .get_mut(&alice)
                 .unwrap()
                 .grants
-                .get_mut(&DEFAULT_CLIFF)
+                .get_mut(&1_000)
                 .unwrap();
-            grant.order_amount = U128::from(100);
+            grant.order_amount = U128::from(5_000);
+            grant.min_buy_bps = Some(8_000);
+        }
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&bob)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+            grant.min_buy_bps = Some(4_000);
         }
 
         context.switch_to_executor();
-        context.with_gas_attached(Gas::from_tgas(1), || {
-            contract.authorize(vec![alice.clone()], Some(10_000));
-        });
+        contract.buy(vec![alice.clone(), bob.clone()], 5_000, false, None);
+
+        let alice_grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(alice_grant.order_amount.0, 5_000);
+        assert_eq!(alice_grant.claimed_amount.0, 0);
+
+        let bob_grant = contract
+            .accounts
+            .get(&bob)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(bob_grant.order_amount.0, 0);
+        assert_eq!(bob_grant.claimed_amount.0, 2_500);
     }
+
     #[rstest]
-    fn on_authorize_complete_reverts_failed_transfers_using_keys(
+    fn atomic_buy_rolls_back_entire_batch_on_any_skip(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
         bob: AccountId,
     ) {
-        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None);
-        contract.create_grant_internal(&bob, DEFAULT_CLIFF, U128::from(1_000), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
+
+        let initial_spare = contract.spare_balance.0;
 
         {
             let grant = contract
@@ -735,103 +2138,137 @@ mod tests {
                 .get_mut(&alice)
                 .unwrap()
                 .grants
-                .get_mut(&DEFAULT_CLIFF)
+                .get_mut(&1_000)
                 .unwrap();
-            grant.claimed_amount = U128::from(100);
+            grant.order_amount = U128::from(5_000);
         }
-
         {
             let grant = contract
                 .accounts
                 .get_mut(&bob)
                 .unwrap()
                 .grants
-                .get_mut(&DEFAULT_CLIFF)
+                .get_mut(&1_000)
                 .unwrap();
-            grant.claimed_amount = U128::from(200);
+            grant.order_amount = U128::from(5_000);
+            grant.min_buy_bps = Some(8_000);
         }
 
-        contract
-            .pending_transfers
-            .insert(alice.clone(), vec![(DEFAULT_CLIFF, U128::from(100))]);
-        contract
-            .pending_transfers
-            .insert(bob.clone(), vec![(DEFAULT_CLIFF, U128::from(200))]);
-
-        context.set_promise_results(vec![
-            PromiseResult::Successful(vec![]),
-            PromiseResult::Failed,
-        ]);
-
-        contract.pause();
-        contract.on_authorize_complete(vec![
-            TransferKey {
-                account_id: alice.clone(),
-                issue_at: DEFAULT_CLIFF,
-            },
-            TransferKey {
-                account_id: bob.clone(),
-                issue_at: DEFAULT_CLIFF,
-            },
-        ]);
+        context.switch_to_executor();
+        contract.buy(vec![alice.clone(), bob.clone()], 5_000, true, None);
 
-        let account_one_state = contract.accounts.get(&alice).unwrap();
-        let grant_one = account_one_state.grants.get(&DEFAULT_CLIFF).unwrap();
-        assert_eq!(grant_one.claimed_amount.0, 100);
-        assert_eq!(grant_one.order_amount.0, 0);
+        let alice_grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(alice_grant.order_amount.0, 5_000);
+        assert_eq!(alice_grant.claimed_amount.0, 0);
 
-        let account_two_state = contract.accounts.get(&bob).unwrap();
-        let grant_two = account_two_state.grants.get(&DEFAULT_CLIFF).unwrap();
-        assert_eq!(grant_two.claimed_amount.0, 0);
-        assert_eq!(grant_two.order_amount.0, 200);
+        let bob_grant = contract
+            .accounts
+            .get(&bob)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(bob_grant.order_amount.0, 5_000);
+        assert_eq!(bob_grant.claimed_amount.0, 0);
 
-        assert!(contract.pending_transfers.is_empty());
+        assert_eq!(contract.spare_balance.0, initial_spare);
     }
 
     #[rstest]
-    fn terminate_respects_cliff(
+    fn buy_with_max_spend_fills_cheapest_reserve_first(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
+        bob: AccountId,
     ) {
-        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(10_000), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
 
         {
-            let account = contract.accounts.get_mut(&alice).unwrap();
-            let grant = account.grants.get_mut(&1_000).unwrap();
-            grant.claimed_amount = U128::from(2_000);
-            grant.order_amount = U128::from(3_000);
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+            grant.min_buy_bps = Some(4_000);
+        }
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&bob)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+            grant.min_buy_bps = Some(2_000);
         }
 
         context.switch_to_executor();
-        contract.terminate(alice.clone(), 1_500);
+        // Both orders would fill at 2_500 each (5_000 * 5_000 / 10_000); a budget that only
+        // covers one should go to bob, whose reserve is cheaper.
+        let unprocessed = contract.buy(
+            vec![alice.clone(), bob.clone()],
+            5_000,
+            false,
+            Some(2_500.into()),
+        );
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&1_000).unwrap();
-        assert_eq!(grant.order_amount.0, 0);
-        assert_eq!(grant.total_amount.0, 2_000);
+        let bob_grant = contract
+            .accounts
+            .get(&bob)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(bob_grant.claimed_amount.0, 2_500);
+        assert_eq!(bob_grant.order_amount.0, 0);
+
+        let alice_grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(alice_grant.claimed_amount.0, 0);
+        assert_eq!(alice_grant.order_amount.0, 5_000);
+
+        assert_eq!(unprocessed, vec![alice]);
     }
 
     #[rstest]
-    fn buy_requires_executor_role(
+    fn buy_with_max_spend_partially_fills_the_order_that_exhausts_the_budget(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
     ) {
-        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(10_000), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
 
         {
-            let account = contract.accounts.get_mut(&alice).unwrap();
-            let grant = account.grants.get_mut(&1_000).unwrap();
-            grant.order_amount = U128::from(4_000);
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
         }
 
-        context.switch_account(&alice);
-        let result = panic::catch_unwind(AssertUnwindSafe(|| {
-            contract.buy(vec![alice.clone()], 5_000);
-        }));
+        context.switch_to_executor();
+        // Full fill would be 2_500; only 1_000 of budget is available.
+        contract.buy(vec![alice.clone()], 5_000, false, Some(1_000.into()));
 
-        assert!(result.is_err());
         let grant = contract
             .accounts
             .get(&alice)
@@ -839,244 +2276,1836 @@ mod tests {
             .grants
             .get(&1_000)
             .unwrap();
+        assert_eq!(grant.claimed_amount.0, 1_000);
         assert_eq!(grant.order_amount.0, 4_000);
-        assert_eq!(grant.claimed_amount.0, 0);
     }
 
     #[rstest]
-    fn terminate_requires_executor_role(
+    fn atomic_buy_refuses_to_start_when_max_spend_cannot_cover_every_order(
         mut context: TestContext,
         mut contract: Contract,
         alice: AccountId,
         bob: AccountId,
     ) {
-        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(10_000), None);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
 
-        context.switch_account(&bob);
-        let result = panic::catch_unwind(AssertUnwindSafe(|| {
-            contract.terminate(alice.clone(), 1_500);
+        let initial_spare = contract.spare_balance.0;
+
+        for account_id in [&alice, &bob] {
+            let grant = contract
+                .accounts
+                .get_mut(account_id)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        context.switch_to_executor();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.buy(
+                vec![alice.clone(), bob.clone()],
+                5_000,
+                true,
+                Some(2_500.into()),
+            );
         }));
 
         assert!(result.is_err());
-        let grant = contract
-            .accounts
-            .get(&alice)
-            .unwrap()
-            .grants
-            .get(&1_000)
-            .unwrap();
-        assert_eq!(grant.total_amount.0, 10_000);
+        assert_eq!(contract.spare_balance.0, initial_spare);
     }
 
-    const GRANT_CLIFF_DURATION: u32 = ONE_YEAR_IN_SECONDS; // 1 year in seconds
-    const GRANT_VESTING_DURATION: u32 = 3 * ONE_YEAR_IN_SECONDS; // 3 years in seconds
-
     #[rstest]
-    fn test_terminate_before_cliff_cancels_order(
+    fn buy_with_no_fillable_order_records_last_error(
         mut context: TestContext,
-        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        mut contract: Contract,
         alice: AccountId,
     ) {
-        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
-        let issue_at = 1_000;
-        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
-        let terminate_at = cliff_end - ONE_DAY_IN_SECONDS;
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
 
-        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None);
-
-        // Claim at 1000 seconds after cliff end
-        context.set_block_timestamp_in_seconds(cliff_end + 1_000);
-        context.switch_account(&alice);
-        contract.claim();
+        context.switch_to_executor();
+        contract.buy(vec![alice.clone()], 5_000, false, None);
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+        assert_eq!(
+            contract.get_last_error(alice.clone()),
+            Some(GrantError::InsufficientOrder.to_string())
+        );
 
-        // Terminate at cliff_end - one day (set block timestamp to termination time)
-        context.switch_to_executor();
-        context.set_block_timestamp_in_seconds(terminate_at);
-        contract.terminate(alice.clone(), terminate_at);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+        contract.buy(vec![alice.clone()], 5_000, false, None);
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.order_amount.0, 0);
-        assert_eq!(grant.total_amount.0, 0);
-        assert_eq!(grant.claimed_amount.0, 0);
+        assert_eq!(contract.get_last_error(alice), None);
     }
 
     #[rstest]
-    fn test_terminate_after_buy_sets_total_to_claimed(
+    fn buy_returns_empty_unprocessed_when_gas_suffices(
         mut context: TestContext,
-        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        mut contract: Contract,
         alice: AccountId,
     ) {
-        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
-        let issue_at = 1_000;
-        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
-
-        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None);
-
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        context.switch_to_executor();
+        let unprocessed = contract.buy(vec![alice.clone()], 5_000, false, None);
+
+        assert!(unprocessed.is_empty());
+    }
+
+    #[rstest]
+    fn buy_returns_unprocessed_tail_without_mutating_state_when_gas_is_insufficient(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
+        for account_id in [&alice, &bob] {
+            let grant = contract
+                .accounts
+                .get_mut(account_id)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        context.switch_to_executor();
+        let unprocessed = context.with_gas_attached(Gas::from_tgas(1), || {
+            contract.buy(vec![alice.clone(), bob.clone()], 5_000, false, None)
+        });
+
+        assert_eq!(unprocessed, vec![alice.clone(), bob.clone()]);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 5_000);
+    }
+
+    #[rstest]
+    fn atomic_buy_refuses_to_start_when_gas_is_insufficient(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        context.switch_to_executor();
+        context.with_gas_attached(Gas::from_tgas(1), || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                contract.buy(vec![alice.clone()], 5_000, true, None);
+            }));
+
+            assert!(result.is_err());
+        });
+    }
+
+    #[rstest]
+    fn set_order_reserve_rejects_invalid_bps(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.set_order_reserve(1_000, Some(10_001));
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn get_orders_applies_filter(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.spare_balance = 3_000.into();
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(2_000);
+        }
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&bob)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        let all_orders = contract.get_orders(OrderFilter::default());
+        assert_eq!(all_orders.len(), 2);
+
+        let payable_orders = contract.get_orders(OrderFilter {
+            payable_only: true,
+            ..Default::default()
+        });
+        assert_eq!(payable_orders.len(), 1);
+        assert_eq!(payable_orders[0].account_id, alice);
+
+        let alice_only = contract.get_orders(OrderFilter {
+            accounts: Some(vec![alice.clone()]),
+            ..Default::default()
+        });
+        assert_eq!(alice_only.len(), 1);
+        assert_eq!(alice_only[0].amount.0, 2_000);
+
+        let min_amount = contract.get_orders(OrderFilter {
+            min_amount: Some(U128::from(3_000)),
+            ..Default::default()
+        });
+        assert_eq!(min_amount.len(), 1);
+        assert_eq!(min_amount[0].account_id, bob);
+    }
+
+    #[rstest]
+    fn issue_reduces_spare_balance(mut context: TestContext, mut contract: Contract) {
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_issuer();
+        context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.issue(
+                1_000,
+                vec![
+                    (accounts(1), U128::from(3_000), None),
+                    (accounts(2), U128::from(2_000), None),
+                ],
+                None,
+            );
+        });
+
+        assert_eq!(contract.spare_balance.0, 5_000);
+        assert!(contract.accounts.get(&accounts(1)).is_some());
+    }
+
+    #[rstest]
+    fn issue_requires_issuer_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.spare_balance = 10_000.into();
+
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.issue(1_000, vec![(bob.clone(), 1_000.into(), None)], None);
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.accounts.get(&bob).is_none());
+        assert_eq!(contract.spare_balance.0, 10_000);
+    }
+
+    #[rstest]
+    fn issue_rejects_zero_amount_grant(
+        mut context: TestContext,
+        mut contract: Contract,
+        bob: AccountId,
+    ) {
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_issuer();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.issue(1_000, vec![(bob.clone(), 0.into(), None)], None);
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.accounts.get(&bob).is_none());
+        assert_eq!(contract.spare_balance.0, 10_000);
+    }
+
+    #[rstest]
+    fn issue_pro_rata_scales_down_and_breaks_ties_by_sorted_account_id(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = 100.into();
+
+        context.switch_to_issuer();
+        let reports = context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.issue(
+                1_000,
+                // Listed charlie, alice, bob - the opposite of sorted order - to prove the
+                // remainder lands on accounts(0) (alice) because it sorts lowest, not because
+                // it happens to be first in the input.
+                vec![
+                    (accounts(2), U128::from(100), None),
+                    (accounts(0), U128::from(100), None),
+                    (accounts(1), U128::from(100), None),
+                ],
+                Some(FillPolicy::ProRata),
+            )
+        });
+
+        assert_eq!(reports[0].account_id, accounts(2));
+        assert_eq!(reports[0].requested_amount.0, 100);
+        assert_eq!(reports[0].issued_amount.0, 33);
+
+        assert_eq!(reports[1].account_id, accounts(0));
+        assert_eq!(reports[1].issued_amount.0, 34);
+
+        assert_eq!(reports[2].account_id, accounts(1));
+        assert_eq!(reports[2].issued_amount.0, 33);
+
+        assert_eq!(contract.spare_balance.0, 0);
+        assert_eq!(
+            contract
+                .accounts
+                .get(&accounts(0))
+                .unwrap()
+                .grants
+                .get(&1_000)
+                .unwrap()
+                .total_amount
+                .0,
+            34
+        );
+    }
+
+    #[rstest]
+    fn issue_priority_order_funds_in_input_order_until_balance_runs_out(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.spare_balance = 100.into();
+
+        context.switch_to_issuer();
+        let reports = context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.issue(
+                1_000,
+                vec![
+                    (alice.clone(), U128::from(70), None),
+                    (bob.clone(), U128::from(50), None),
+                ],
+                Some(FillPolicy::PriorityOrder),
+            )
+        });
+
+        assert_eq!(reports[0].issued_amount.0, 70);
+        assert_eq!(reports[1].issued_amount.0, 0);
+
+        assert_eq!(contract.spare_balance.0, 30);
+        assert!(contract.accounts.get(&alice).is_some());
+        assert!(contract.accounts.get(&bob).is_none());
+    }
+
+    #[rstest]
+    fn issue_all_or_nothing_still_panics_when_oversubscribed(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.spare_balance = 100.into();
+
+        context.switch_to_issuer();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.issue(1_000, vec![(alice.clone(), 200.into(), None)], None);
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.accounts.get(&alice).is_none());
+        assert_eq!(contract.spare_balance.0, 100);
+    }
+
+    #[rstest]
+    fn authorize_moves_order_into_pending_transfers(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(4_000);
+        }
+
+        context.switch_to_executor();
+        contract.authorize(vec![alice.clone()], Some(5_000), false);
+
+        let pending = contract.get_pending_transfers();
+        assert!(pending.contains_key(&alice));
+        let transfers = pending.get(&alice).unwrap();
+        assert_eq!(transfers[0].amount.0, 2_000);
+        assert_eq!(transfers[0].authorized_bps, 5_000);
+    }
+
+    #[rstest]
+    fn authorize_with_fee_schedule_splits_grantee_transfer_and_flushes_collector_leg(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        use crate::{config::ConfigApi, FeeSchedule};
+
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(4_000);
+        }
+
+        context.switch_account(&owner);
+        contract.set_fee_schedule(Some(FeeSchedule {
+            buyback_bps: 0,
+            authorize_bps: 1_000,
+            fee_collector: bob.clone(),
+        }));
+
+        context.switch_to_executor();
+        contract.authorize(vec![alice.clone()], Some(5_000), false);
+
+        // authorized_amount = 4_000 * 50% = 2_000; `pending_amount` still tracks the full amount
+        // moved out of `order_amount`, even though the grantee's own transfer leg is smaller.
+        let pending = contract.get_pending_transfers();
+        let transfers = pending.get(&alice).unwrap();
+        assert_eq!(transfers[0].amount.0, 2_000);
+
+        // The batch's fee leg is flushed (and accrued_fees reset) synchronously while building
+        // the transfer batch, so nothing is left outstanding once `authorize` returns.
+        assert_eq!(contract.accrued_fees.0, 0);
+    }
+
+    #[rstest]
+    fn get_pending_transfers_reports_accrued_fee_collector_entry(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        use crate::{config::ConfigApi, FeeSchedule};
+
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(5_000);
+        }
+
+        context.switch_account(&owner);
+        contract.set_fee_schedule(Some(FeeSchedule {
+            buyback_bps: 1_000,
+            authorize_bps: 2_000,
+            fee_collector: bob.clone(),
+        }));
+
+        // `buy` never creates a Promise, so its fee has nowhere to flush to yet and simply
+        // accrues on the contract until the next `authorize` batch runs.
+        context.switch_to_executor();
+        contract.buy(vec![alice.clone()], 5_000, false, None);
+
+        let pending = contract.get_pending_transfers();
+        let collector_entries = pending.get(&bob).unwrap();
+        assert_eq!(collector_entries.len(), 1);
+        assert_eq!(collector_entries[0].amount.0, 250);
+        assert_eq!(collector_entries[0].authorized_bps, 2_000);
+    }
+
+    #[rstest]
+    fn require_all_authorize_rolls_back_entire_batch_when_one_account_has_no_order(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(4_000);
+        }
+        // bob has no order_amount at all, so the batch has nothing to authorize for him.
+
+        context.switch_to_executor();
+        contract.authorize(vec![alice.clone(), bob.clone()], Some(5_000), true);
+
+        assert!(contract.get_pending_transfers().is_empty());
+        let account = contract.accounts.get_mut(&alice).unwrap();
+        let grant = account.grants.get_mut(&1_000).unwrap();
+        assert_eq!(grant.order_amount.0, 4_000);
+        assert!(!grant.locked);
+        assert_eq!(
+            contract.get_last_error(alice),
+            Some(GrantError::InsufficientOrder.to_string())
+        );
+    }
+
+    #[rstest]
+    fn require_all_authorize_proceeds_when_every_account_has_a_fillable_order(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.create_grant_internal(&bob, 1_000, 10_000.into(), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(4_000);
+        }
+        {
+            let account = contract.accounts.get_mut(&bob).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(2_000);
+        }
+
+        context.switch_to_executor();
+        contract.authorize(vec![alice.clone(), bob.clone()], Some(5_000), true);
+
+        let pending = contract.get_pending_transfers();
+        assert!(pending.contains_key(&alice));
+        assert!(pending.contains_key(&bob));
+    }
+
+    #[rstest]
+    fn authorize_requires_executor_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.authorize(vec![alice.clone()], Some(10_000), false);
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.get_pending_transfers().is_empty());
+    }
+
+    #[rstest]
+    #[should_panic(expected = "Not enough gas left.")]
+    fn authorize_fails_with_insufficient_gas(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.order_amount = U128::from(100);
+        }
+
+        context.switch_to_executor();
+        context.with_gas_attached(Gas::from_tgas(1), || {
+            contract.authorize(vec![alice.clone()], Some(10_000), false);
+        });
+    }
+    #[rstest]
+    fn on_authorize_complete_reverts_failed_transfers_using_keys(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        contract.create_grant_internal(&bob, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.claimed_amount = U128::from(100);
+            grant.locked = true;
+            grant.pending_amount = U128::from(100);
+        }
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&bob)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.claimed_amount = U128::from(200);
+            grant.locked = true;
+            grant.pending_amount = U128::from(200);
+        }
+
+        context.set_promise_results(vec![
+            PromiseResult::Successful(vec![]),
+            PromiseResult::Failed,
+        ]);
+
+        contract.on_authorize_complete(
+            vec![
+                TransferKey {
+                    account_id: alice.clone(),
+                    issue_at: DEFAULT_CLIFF,
+                },
+                TransferKey {
+                    account_id: bob.clone(),
+                    issue_at: DEFAULT_CLIFF,
+                },
+            ],
+            GAS_FOR_FT_TRANSFER_CALL.as_gas(),
+            U128::from(0),
+        );
+
+        let account_one_state = contract.accounts.get(&alice).unwrap();
+        let grant_one = account_one_state.grants.get(&DEFAULT_CLIFF).unwrap();
+        assert_eq!(grant_one.claimed_amount.0, 200);
+        assert_eq!(grant_one.order_amount.0, 0);
+
+        let account_two_state = contract.accounts.get(&bob).unwrap();
+        let grant_two = account_two_state.grants.get(&DEFAULT_CLIFF).unwrap();
+        assert_eq!(grant_two.claimed_amount.0, 200);
+        assert_eq!(grant_two.order_amount.0, 200);
+
+        assert!(!grant_one.locked);
+        assert!(!grant_two.locked);
+        assert!(contract.get_pending_transfers().is_empty());
+
+        let failed = contract.get_failed_transfers();
+        assert!(!failed.contains_key(&alice));
+        let bob_failed = failed.get(&bob).unwrap();
+        assert_eq!(bob_failed.len(), 1);
+        assert_eq!(bob_failed[0].amount.0, 200);
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, 1);
+    }
+
+    #[rstest]
+    fn retry_failed_re_authorizes_the_restored_order_amount(
+        mut context: TestContext,
+        mut contract: Contract,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&bob, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&bob)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.locked = true;
+            grant.pending_amount = U128::from(200);
+            grant.locked_bps = Some(5_000);
+        }
+
+        context.set_promise_results(vec![PromiseResult::Failed]);
+        contract.on_authorize_complete(
+            vec![TransferKey {
+                account_id: bob.clone(),
+                issue_at: DEFAULT_CLIFF,
+            }],
+            GAS_FOR_FT_TRANSFER_CALL.as_gas(),
+            U128::from(0),
+        );
+
+        assert!(contract.get_failed_transfers().contains_key(&bob));
+        assert_eq!(
+            contract
+                .accounts
+                .get(&bob)
+                .unwrap()
+                .grants
+                .get(&DEFAULT_CLIFF)
+                .unwrap()
+                .order_amount
+                .0,
+            200
+        );
+
+        context.switch_to_executor();
+        contract.retry_failed(vec![bob.clone()]);
+
+        assert!(!contract.get_failed_transfers().contains_key(&bob));
+        let pending = contract.get_pending_transfers();
+        let transfers = pending.get(&bob).unwrap();
+        assert_eq!(transfers[0].amount.0, 100);
+        assert_eq!(transfers[0].authorized_bps, 5_000);
+    }
+
+    #[rstest]
+    fn on_authorize_complete_restores_accrued_fees_when_the_fee_leg_fails(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.claimed_amount = U128::from(100);
+            grant.locked = true;
+            grant.pending_amount = U128::from(100);
+        }
+
+        // One grantee leg (index 0) followed by the trailing fee-collector leg (index 1), which
+        // fails here.
+        context.set_promise_results(vec![
+            PromiseResult::Successful(vec![]),
+            PromiseResult::Failed,
+        ]);
+
+        contract.on_authorize_complete(
+            vec![TransferKey {
+                account_id: alice.clone(),
+                issue_at: DEFAULT_CLIFF,
+            }],
+            GAS_FOR_FT_TRANSFER_CALL.as_gas(),
+            U128::from(20),
+        );
+
+        assert_eq!(contract.accrued_fees.0, 20);
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, 1);
+    }
+
+    #[rstest]
+    fn reconcile_pending_transfer_restores_order_amount_after_stuck_blocks(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        context.set_block_height(100);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.locked = true;
+            grant.pending_amount = U128::from(400);
+            grant.locked_batch_id = Some(1);
+            grant.locked_at_block_height = Some(100);
+        }
+
+        context.switch_to_executor();
+        context.set_block_height(100 + super::STUCK_TRANSFER_BLOCKS);
+        contract.reconcile_pending_transfer(alice.clone(), DEFAULT_CLIFF, 1);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert!(!grant.locked);
+        assert_eq!(grant.order_amount.0, 400);
+        assert_eq!(grant.pending_amount.0, 0);
+
+        // Calling again is a no-op since the grant is no longer locked under batch 1.
+        contract.reconcile_pending_transfer(alice.clone(), DEFAULT_CLIFF, 1);
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 400);
+    }
+
+    #[rstest]
+    #[should_panic(expected = "Batch hasn't been stuck long enough")]
+    fn reconcile_pending_transfer_rejects_premature_call(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        context.set_block_height(100);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.locked = true;
+            grant.pending_amount = U128::from(400);
+            grant.locked_batch_id = Some(1);
+            grant.locked_at_block_height = Some(100);
+        }
+
+        context.switch_to_executor();
+        contract.reconcile_pending_transfer(alice.clone(), DEFAULT_CLIFF, 1);
+    }
+
+    #[rstest]
+    fn terminate_respects_cliff(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(10_000), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.claimed_amount = U128::from(2_000);
+            grant.order_amount = U128::from(3_000);
+        }
+
+        context.switch_to_executor();
+        contract.terminate(alice.clone(), 1_500);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.order_amount.0, 0);
+        assert_eq!(grant.total_amount.0, 2_000);
+    }
+
+    #[rstest]
+    fn terminate_adds_unvested_amount_to_forfeited_total(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(10_000), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.claimed_amount = U128::from(2_000);
+        }
+
+        let initial_spare = contract.spare_balance.0;
+
+        context.switch_to_executor();
+        contract.terminate(alice.clone(), 1_500);
+
+        let forfeited = contract.get_forfeited_total().0;
+        assert!(forfeited > 0);
+        assert_eq!(contract.spare_balance.0, initial_spare + forfeited);
+    }
+
+    #[rstest]
+    fn buy_requires_executor_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(10_000), None, None);
+
+        {
+            let account = contract.accounts.get_mut(&alice).unwrap();
+            let grant = account.grants.get_mut(&1_000).unwrap();
+            grant.order_amount = U128::from(4_000);
+        }
+
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.buy(vec![alice.clone()], 5_000, false, None);
+        }));
+
+        assert!(result.is_err());
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 4_000);
+        assert_eq!(grant.claimed_amount.0, 0);
+    }
+
+    #[rstest]
+    fn terminate_requires_executor_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(10_000), None, None);
+
+        context.switch_account(&bob);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.terminate(alice.clone(), 1_500);
+        }));
+
+        assert!(result.is_err());
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(grant.total_amount.0, 10_000);
+    }
+
+    #[rstest]
+    fn try_require_role_active_rejects_an_account_without_the_role(
+        mut context: TestContext,
+        contract: Contract,
+        alice: AccountId,
+    ) {
+        context.switch_account(&alice);
+        assert_eq!(
+            contract.try_require_role_active(&Role::Executor),
+            Err(GrantError::Unauthorized)
+        );
+    }
+
+    #[rstest]
+    fn try_require_role_active_accepts_the_executor(mut context: TestContext, contract: Contract) {
+        context.switch_to_executor();
+        assert_eq!(contract.try_require_role_active(&Role::Executor), Ok(()));
+    }
+
+    #[rstest]
+    fn try_require_operation_unpaused_rejects_a_frozen_operation(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+    ) {
+        use crate::auth::{AuthApi, Operation};
+
+        context.switch_account(&owner);
+        contract.pause_operation(Operation::Buy);
+
+        assert_eq!(
+            contract.try_require_operation_unpaused(Operation::Buy),
+            Err(GrantError::ContractPaused)
+        );
+    }
+
+    const GRANT_CLIFF_DURATION: u32 = ONE_YEAR_IN_SECONDS; // 1 year in seconds
+    const GRANT_VESTING_DURATION: u32 = 3 * ONE_YEAR_IN_SECONDS; // 3 years in seconds
+
+    #[rstest]
+    fn test_terminate_before_cliff_cancels_order(
+        mut context: TestContext,
+        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
+        let issue_at = 1_000;
+        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
+        let terminate_at = cliff_end - ONE_DAY_IN_SECONDS;
+
+        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None, None);
+
+        // Claim at 1000 seconds after cliff end
+        context.set_block_timestamp_in_seconds(cliff_end + 1_000);
+        context.switch_account(&alice);
+        contract.claim();
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+
+        // Terminate at cliff_end - one day (set block timestamp to termination time)
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(terminate_at);
+        contract.terminate(alice.clone(), terminate_at);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.order_amount.0, 0);
+        assert_eq!(grant.total_amount.0, 0);
+        assert_eq!(grant.claimed_amount.0, 0);
+    }
+
+    #[rstest]
+    fn test_terminate_after_buy_sets_total_to_claimed(
+        mut context: TestContext,
+        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
+        let issue_at = 1_000;
+        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
+
+        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None, None);
+
+        // Claim at 1000 seconds after cliff end
+        context.set_block_timestamp_in_seconds(cliff_end + 1_000);
+        context.switch_account(&alice);
+        contract.claim();
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+
+        // Executor buys 100% of the order
+        context.switch_to_executor();
+        contract.buy(vec![alice.clone()], 10_000, false, None); // 100%
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.claimed_amount.0, 1_000u128.to_otto());
+        assert_eq!(grant.order_amount.0, 0);
+
+        // 1000 seconds later, terminate the grant at the timestamp when vested equals claimed
+        // (terminate at cliff_end + 1000 to get total = claimed = 1000)
+        let terminate_at = cliff_end + 1_000;
+        context.set_block_timestamp_in_seconds(terminate_at);
+        contract.terminate(alice.clone(), terminate_at);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.total_amount.0, 1_000u128.to_otto());
+        assert_eq!(grant.claimed_amount.0, 1_000u128.to_otto());
+        assert_eq!(grant.order_amount.0, 0);
+    }
+
+    #[rstest]
+    fn test_terminate_cuts_order_to_vested_amount(
+        mut context: TestContext,
+        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
+        let issue_at = 1_000;
+        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
+        let terminate_at = cliff_end + 500;
+
+        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None, None);
+
+        // Claim at 1000 seconds after cliff end
+        context.set_block_timestamp_in_seconds(cliff_end + 1_000);
+        context.switch_account(&alice);
+        contract.claim();
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+
+        // Terminate at 500 seconds after cliff end (cutting the order)
+        // Set block timestamp to termination time so vested calculation uses that
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(terminate_at);
+        contract.terminate(alice.clone(), terminate_at);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.order_amount.0, 500u128.to_otto());
+        assert_eq!(grant.total_amount.0, 500u128.to_otto());
+    }
+
+    #[rstest]
+    fn test_terminate_after_buy_preserves_claimed_amount(
+        mut context: TestContext,
+        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
+        let issue_at = 1_000;
+        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
+        let terminate_at = cliff_end + 500;
+
+        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None, None);
+
         // Claim at 1000 seconds after cliff end
         context.set_block_timestamp_in_seconds(cliff_end + 1_000);
         context.switch_account(&alice);
         contract.claim();
 
         let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+
+        // Executor buys 100% of the order
+        context.switch_to_executor();
+        contract.buy(vec![alice.clone()], 10_000, false, None); // 100%
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.claimed_amount.0, 1_000u128.to_otto());
+
+        // Terminate at 500 seconds after cliff end
+        // Set block timestamp to termination time
+        context.set_block_timestamp_in_seconds(terminate_at);
+        contract.terminate(alice.clone(), terminate_at);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.total_amount.0, 1_000u128.to_otto());
+    }
+
+    #[rstest]
+    fn test_terminate_before_cliff_sets_total_to_zero(
+        mut context: TestContext,
+        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
+        let issue_at = 1_000;
+        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
+        let terminate_at = cliff_end - 1_000;
+
+        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None, None);
+
+        // Terminate 1000 seconds before cliff end
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(terminate_at);
+        contract.terminate(alice.clone(), terminate_at);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.total_amount.0, 0);
+    }
+
+    #[rstest]
+    fn test_terminate_twice_fails(
+        mut context: TestContext,
+        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
+        let issue_at = 1_000;
+        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
+
+        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None, None);
+
+        // Terminate 5000 seconds after cliff end
+        context.switch_to_executor();
+        let first_terminate_at = cliff_end + 5_000;
+        context.set_block_timestamp_in_seconds(first_terminate_at);
+        contract.terminate(alice.clone(), first_terminate_at);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        assert_eq!(grant.total_amount.0, 5_000u128.to_otto());
+        assert_eq!(contract.get_last_error(alice.clone()), None);
+
+        // Try to terminate again at 1000 seconds after cliff (should fail/no-op)
+        // The terminate function returns early if already terminated, so it doesn't panic
+        // but the state shouldn't change
+        let second_terminate_at = cliff_end + 1_000;
+        context.set_block_timestamp_in_seconds(second_terminate_at);
+        contract.terminate(alice.clone(), second_terminate_at);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&issue_at).unwrap();
+        // Should remain unchanged (still at 5000)
+        assert_eq!(grant.total_amount.0, 5_000u128.to_otto());
+        assert_eq!(
+            contract.get_last_error(alice),
+            Some(GrantError::AlreadyTerminated.to_string())
+        );
+    }
+
+    #[rstest]
+    fn terminate_vesting_only_affects_the_targeted_grant(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, U128::from(10_000), None, None);
+        contract.create_grant_internal(&alice, 2_000, U128::from(10_000), None, None);
+
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(3_000);
+        contract.terminate_vesting(alice.clone(), 1_000);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        assert!(account.grants.get(&1_000).unwrap().terminated_at.is_some());
+        assert!(account.grants.get(&2_000).unwrap().terminated_at.is_none());
+        assert_eq!(contract.get_last_error(alice), None);
+    }
+
+    #[rstest]
+    fn terminate_vesting_credits_unvested_amount_to_spare_balance(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, U128::from(10_000), None, None);
+        let initial_spare = contract.spare_balance.0;
+
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(3_000);
+        contract.terminate_vesting(alice.clone(), 1_000);
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.total_amount.0, 5_000);
+        assert_eq!(contract.spare_balance.0, initial_spare + 5_000);
+        assert_eq!(contract.get_forfeited_total().0, 5_000);
+    }
+
+    #[rstest]
+    fn terminate_vesting_requires_executor_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, U128::from(10_000), None, None);
+
+        context.switch_account(&bob);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.terminate_vesting(alice.clone(), 1_000);
+        }));
+
+        assert!(result.is_err());
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap();
+        assert_eq!(grant.total_amount.0, 10_000);
+    }
+
+    #[rstest]
+    fn terminate_vesting_twice_records_last_error(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, U128::from(10_000), None, None);
+
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(1_500);
+        contract.terminate_vesting(alice.clone(), 1_000);
+        assert_eq!(contract.get_last_error(alice.clone()), None);
+
+        contract.terminate_vesting(alice.clone(), 1_000);
+        assert_eq!(
+            contract.get_last_error(alice),
+            Some(GrantError::AlreadyTerminated.to_string())
+        );
+    }
+
+    #[rstest]
+    fn terminate_vesting_on_missing_grant_records_last_error(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        context.switch_to_executor();
+        contract.terminate_vesting(alice.clone(), 1_000);
+
+        assert_eq!(
+            contract.get_last_error(alice),
+            Some(GrantError::AlreadyTerminated.to_string())
+        );
+    }
+
+    #[rstest]
+    fn prune_removes_fully_claimed_grant_and_empty_account(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.claimed_amount = U128::from(10_000);
+        }
+
+        context.switch_to_executor();
+        contract.prune(vec![alice.clone()], U128::from(0));
+
+        assert!(contract.accounts.get(&alice).is_none());
+    }
+
+    #[rstest]
+    fn prune_collapses_grant_within_dust_threshold(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.claimed_amount = U128::from(9_995);
+        }
+
+        context.switch_to_executor();
+        contract.prune(vec![alice.clone()], U128::from(5));
+
+        assert!(contract.accounts.get(&alice).is_none());
+    }
+
+    #[rstest]
+    fn prune_skips_grant_with_outstanding_order(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.claimed_amount = U128::from(10_000);
+            grant.order_amount = U128::from(1);
+        }
+
+        context.switch_to_executor();
+        contract.prune(vec![alice.clone()], U128::from(0));
+
+        assert!(contract.accounts.get(&alice).is_some());
+    }
+
+    #[rstest]
+    fn prune_skips_locked_grant(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.claimed_amount = U128::from(10_000);
+            grant.locked = true;
+        }
+
+        context.switch_to_executor();
+        contract.prune(vec![alice.clone()], U128::from(0));
+
+        assert!(contract.accounts.get(&alice).is_some());
+    }
+
+    #[rstest]
+    fn prune_requires_executor_role(mut contract: Contract, alice: AccountId) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.prune(vec![alice.clone()], U128::from(0));
+        }));
+
+        assert!(result.is_err());
+    }
+
+    fn milestone_schedule(alice: &AccountId) -> Schedule {
+        Schedule::Milestone {
+            tranches: vec![
+                Tranche {
+                    amount: U128::from(4_000),
+                    unlock_after: 2_000,
+                    approver: None,
+                    released: false,
+                },
+                Tranche {
+                    amount: U128::from(6_000),
+                    unlock_after: 3_000,
+                    approver: Some(alice.clone()),
+                    released: false,
+                },
+            ],
+        }
+    }
+
+    #[rstest]
+    fn milestone_tranche_only_vests_once_released_and_unlocked(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let schedule = milestone_schedule(&alice);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, Some(schedule));
+
+        context.set_block_timestamp_in_seconds(5_000);
+        context.switch_account(&alice);
+        contract.claim();
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.order_amount.0, 0);
+    }
+
+    #[rstest]
+    fn create_grant_rejects_milestone_tranches_not_summing_to_total_amount(
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        let schedule = Schedule::Milestone {
+            tranches: vec![Tranche {
+                amount: U128::from(4_000),
+                unlock_after: 2_000,
+                approver: None,
+                released: false,
+            }],
+        };
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, Some(schedule));
+        }));
+
+        assert!(result.is_err());
+        assert!(!contract
+            .accounts
+            .get(&alice)
+            .is_some_and(|account| account.grants.contains_key(&1_000)));
+    }
+
+    #[rstest]
+    fn confirm_tranche_without_approver_is_open_to_anyone(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        let schedule = milestone_schedule(&alice);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, Some(schedule));
+
+        context.switch_account(&bob);
+        contract.confirm_tranche(alice.clone(), 1_000, 0);
+
+        context.set_block_timestamp_in_seconds(2_000);
+        context.switch_account(&alice);
+        contract.claim();
+
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.order_amount.0, 4_000);
+    }
 
-        // Executor buys 100% of the order
-        context.switch_to_executor();
-        contract.buy(vec![alice.clone()], 10_000); // 100%
+    #[rstest]
+    fn confirm_tranche_requires_designated_approver(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        let schedule = milestone_schedule(&alice);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, Some(schedule));
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.claimed_amount.0, 1_000u128.to_otto());
-        assert_eq!(grant.order_amount.0, 0);
+        context.switch_account(&bob);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.confirm_tranche(alice.clone(), 1_000, 1);
+        }));
 
-        // 1000 seconds later, terminate the grant at the timestamp when vested equals claimed
-        // (terminate at cliff_end + 1000 to get total = claimed = 1000)
-        let terminate_at = cliff_end + 1_000;
-        context.set_block_timestamp_in_seconds(terminate_at);
-        contract.terminate(alice.clone(), terminate_at);
+        assert!(result.is_err());
+
+        context.switch_account(&alice);
+        contract.confirm_tranche(alice.clone(), 1_000, 1);
+
+        context.set_block_timestamp_in_seconds(3_000);
+        contract.claim();
 
         let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.total_amount.0, 1_000u128.to_otto());
-        assert_eq!(grant.claimed_amount.0, 1_000u128.to_otto());
-        assert_eq!(grant.order_amount.0, 0);
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.order_amount.0, 6_000);
     }
 
     #[rstest]
-    fn test_terminate_cuts_order_to_vested_amount(
+    fn issue_with_per_grant_schedule_override_resolves_independently_of_config(
         mut context: TestContext,
-        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        mut contract: Contract,
         alice: AccountId,
+        bob: AccountId,
     ) {
-        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
-        let issue_at = 1_000;
-        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
-        let terminate_at = cliff_end + 500;
-
-        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None);
+        contract.spare_balance = 20_000.into();
 
-        // Claim at 1000 seconds after cliff end
-        context.set_block_timestamp_in_seconds(cliff_end + 1_000);
-        context.switch_account(&alice);
-        contract.claim();
+        context.switch_to_issuer();
+        context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.issue(
+                1_000,
+                vec![
+                    (alice.clone(), U128::from(10_000), None),
+                    (
+                        bob.clone(),
+                        U128::from(10_000),
+                        Some(Schedule::linear(1_000, 5_000, 10_000)),
+                    ),
+                ],
+                None,
+            );
+        });
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+        // alice falls back to the contract-wide Config curve (DEFAULT_CLIFF_DURATION/DEFAULT_VESTING_DURATION).
+        assert_eq!(
+            contract.get_grant_schedule(&alice, 1_000),
+            Some((
+                1_000 + DEFAULT_CLIFF_DURATION,
+                1_000 + DEFAULT_CLIFF_DURATION + DEFAULT_VESTING_DURATION
+            ))
+        );
+        // bob carries his own per-grant override instead.
+        assert_eq!(
+            contract.get_grant_schedule(&bob, 1_000),
+            Some((6_000, 16_000))
+        );
+    }
 
-        // Terminate at 500 seconds after cliff end (cutting the order)
-        // Set block timestamp to termination time so vested calculation uses that
-        context.switch_to_executor();
-        context.set_block_timestamp_in_seconds(terminate_at);
-        contract.terminate(alice.clone(), terminate_at);
+    #[rstest]
+    fn get_grant_schedule_is_none_for_milestone_grants(mut contract: Contract, alice: AccountId) {
+        let schedule = milestone_schedule(&alice);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, Some(schedule));
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.order_amount.0, 500u128.to_otto());
-        assert_eq!(grant.total_amount.0, 500u128.to_otto());
+        assert_eq!(contract.get_grant_schedule(&alice, 1_000), None);
+        assert_eq!(contract.get_grant_schedule(&alice, 999), None);
     }
 
     #[rstest]
-    fn test_terminate_after_buy_preserves_claimed_amount(
+    fn update_lockup_extends_cliff_and_vesting_end(
         mut context: TestContext,
-        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        mut contract: Contract,
         alice: AccountId,
+        bob: AccountId,
     ) {
-        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
-        let issue_at = 1_000;
-        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
-        let terminate_at = cliff_end + 500;
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.add_role(&bob, &Role::Custodian);
 
-        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None);
+        context.switch_account(&bob);
+        contract.update_lockup(
+            alice.clone(),
+            1_000,
+            Schedule::Linear {
+                cliff_end: 1_000 + DEFAULT_CLIFF_DURATION + 500,
+                vesting_end: 1_000 + DEFAULT_CLIFF_DURATION + DEFAULT_VESTING_DURATION + 500,
+            },
+        );
 
-        // Claim at 1000 seconds after cliff end
-        context.set_block_timestamp_in_seconds(cliff_end + 1_000);
-        context.switch_account(&alice);
-        contract.claim();
+        assert_eq!(
+            contract.get_grant_schedule(&alice, 1_000),
+            Some((
+                1_000 + DEFAULT_CLIFF_DURATION + 500,
+                1_000 + DEFAULT_CLIFF_DURATION + DEFAULT_VESTING_DURATION + 500
+            ))
+        );
+    }
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.order_amount.0, 1_000u128.to_otto());
+    #[rstest]
+    fn update_lockup_rejects_shortening_the_lockup(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        contract.add_role(&bob, &Role::Custodian);
+        let original_schedule = contract.get_grant_schedule(&alice, 1_000).unwrap();
 
-        // Executor buys 100% of the order
-        context.switch_to_executor();
-        contract.buy(vec![alice.clone()], 10_000); // 100%
+        context.switch_account(&bob);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.update_lockup(
+                alice.clone(),
+                1_000,
+                Schedule::Linear {
+                    cliff_end: original_schedule.0 - 1,
+                    vesting_end: original_schedule.1,
+                },
+            );
+        }));
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.claimed_amount.0, 1_000u128.to_otto());
+        assert!(result.is_err());
+        assert_eq!(
+            contract.get_grant_schedule(&alice, 1_000),
+            Some(original_schedule)
+        );
+    }
 
-        // Terminate at 500 seconds after cliff end
-        // Set block timestamp to termination time
-        context.set_block_timestamp_in_seconds(terminate_at);
-        contract.terminate(alice.clone(), terminate_at);
+    #[rstest]
+    fn update_lockup_requires_custodian_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        let original_schedule = contract.get_grant_schedule(&alice, 1_000).unwrap();
 
-        let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.total_amount.0, 1_000u128.to_otto());
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.update_lockup(
+                alice.clone(),
+                1_000,
+                Schedule::Linear {
+                    cliff_end: original_schedule.0 + 500,
+                    vesting_end: original_schedule.1 + 500,
+                },
+            );
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(
+            contract.get_grant_schedule(&alice, 1_000),
+            Some(original_schedule)
+        );
     }
 
     #[rstest]
-    fn test_terminate_before_cliff_sets_total_to_zero(
+    fn update_lockup_rejects_milestone_grants(
         mut context: TestContext,
-        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        mut contract: Contract,
         alice: AccountId,
+        bob: AccountId,
     ) {
-        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
-        let issue_at = 1_000;
-        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
-        let terminate_at = cliff_end - 1_000;
+        let schedule = milestone_schedule(&alice);
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, Some(schedule));
+        contract.add_role(&bob, &Role::Custodian);
 
-        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None);
+        context.switch_account(&bob);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.update_lockup(alice.clone(), 1_000, Schedule::linear(1_000, 5_000, 10_000));
+        }));
 
-        // Terminate 1000 seconds before cliff end
-        context.switch_to_executor();
-        context.set_block_timestamp_in_seconds(terminate_at);
-        contract.terminate(alice.clone(), terminate_at);
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn claim_skips_grant_with_unmet_conditions(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.conditions = vec![Condition::Oracle { reporter: bob }];
+        }
+
+        context.set_block_timestamp_in_seconds(4_000);
+        context.switch_account(&alice);
+        contract.claim();
 
         let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.total_amount.0, 0);
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.order_amount.0, 0);
     }
 
     #[rstest]
-    fn test_terminate_twice_fails(
+    fn apply_witness_clears_oracle_condition_and_unlocks_claim(
         mut context: TestContext,
-        #[with(GRANT_CLIFF_DURATION, GRANT_VESTING_DURATION)] mut contract: Contract,
+        mut contract: Contract,
         alice: AccountId,
+        bob: AccountId,
     ) {
-        let grant_amount = 94_670_856u128.to_otto(); // 94670856 tokens
-        let issue_at = 1_000;
-        let cliff_end = issue_at + GRANT_CLIFF_DURATION;
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.conditions = vec![Condition::Oracle {
+                reporter: bob.clone(),
+            }];
+        }
 
-        contract.create_grant_internal(&alice, issue_at, grant_amount.into(), None);
+        context.switch_account(&alice);
+        contract.apply_witness(alice.clone(), 1_000);
 
-        // Terminate 5000 seconds after cliff end
-        context.switch_to_executor();
-        let first_terminate_at = cliff_end + 5_000;
-        context.set_block_timestamp_in_seconds(first_terminate_at);
-        contract.terminate(alice.clone(), first_terminate_at);
+        let account = contract.accounts.get(&alice).unwrap();
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.conditions.len(), 1);
+
+        context.switch_account(&bob);
+        contract.apply_witness(alice.clone(), 1_000);
 
         let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        assert_eq!(grant.total_amount.0, 5_000u128.to_otto());
+        let grant = account.grants.get(&1_000).unwrap();
+        assert!(grant.conditions.is_empty());
 
-        // Try to terminate again at 1000 seconds after cliff (should fail/no-op)
-        // The terminate function returns early if already terminated, so it doesn't panic
-        // but the state shouldn't change
-        let second_terminate_at = cliff_end + 1_000;
-        context.set_block_timestamp_in_seconds(second_terminate_at);
-        contract.terminate(alice.clone(), second_terminate_at);
+        context.set_block_timestamp_in_seconds(4_000);
+        context.switch_account(&alice);
+        contract.claim();
 
         let account = contract.accounts.get(&alice).unwrap();
-        let grant = account.grants.get(&issue_at).unwrap();
-        // Should remain unchanged (still at 5000)
-        assert_eq!(grant.total_amount.0, 5_000u128.to_otto());
+        let grant = account.grants.get(&1_000).unwrap();
+        assert_eq!(grant.order_amount.0, 10_000);
+    }
+
+    #[rstest]
+    fn apply_witness_clears_timestamp_condition_once_due(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.conditions = vec![Condition::Timestamp(5_000)];
+        }
+
+        context.set_block_timestamp_in_seconds(4_000);
+        contract.apply_witness(alice.clone(), 1_000);
+        assert_eq!(
+            contract
+                .accounts
+                .get(&alice)
+                .unwrap()
+                .grants
+                .get(&1_000)
+                .unwrap()
+                .conditions
+                .len(),
+            1
+        );
+
+        context.set_block_timestamp_in_seconds(5_000);
+        contract.apply_witness(alice.clone(), 1_000);
+        assert!(contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&1_000)
+            .unwrap()
+            .conditions
+            .is_empty());
+    }
+
+    #[rstest]
+    fn get_account_enumerates_outstanding_conditions(
+        mut contract: Contract,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, 1_000, 10_000.into(), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&1_000)
+                .unwrap();
+            grant.conditions = vec![
+                Condition::Timestamp(5_000),
+                Condition::Oracle {
+                    reporter: bob.clone(),
+                },
+            ];
+        }
+
+        let view = contract.get_account(&alice).unwrap();
+        assert_eq!(
+            view.grants[0].conditions,
+            vec![
+                Condition::Timestamp(5_000),
+                Condition::Oracle { reporter: bob }
+            ]
+        );
     }
 }
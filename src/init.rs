@@ -1,4 +1,6 @@
-use crate::{Config, Contract, ContractExt, StorageKey};
+use std::collections::HashSet;
+
+use crate::{Config, Contract, ContractExt, Role, StorageKey};
 use near_sdk::{near, store::IterableMap, AccountId};
 use near_sdk_contract_tools::owner::Owner;
 
@@ -10,7 +12,17 @@ pub trait InitApi {
         cliff_duration: u32,
         vestin_duration: u32,
         owner_id: AccountId,
+        token_decimals: u8,
     ) -> Contract;
+
+    /// Post-upgrade maintenance entrypoint: the owner calls this once after deploying new
+    /// contract code via `Upgrade::upgrade`, listing every `Role` variant the new code still
+    /// defines. Any RBAC assignment for a role `still_valid` omits is revoked (with a
+    /// `RoleRevoked` event apiece), cleaning up entries a removed or renamed `Role` variant would
+    /// otherwise leave stranded in storage with unintended standing privilege. Returns how many
+    /// assignments were revoked. Passing every currently-defined variant is a safe no-op health
+    /// check.
+    fn migrate(&mut self, still_valid: Vec<Role>) -> u32;
 }
 
 #[near]
@@ -22,6 +34,7 @@ impl InitApi for Contract {
         cliff_duration: u32,
         vesting_duration: u32,
         owner_id: AccountId,
+        token_decimals: u8,
     ) -> Contract {
         let mut contract = Contract {
             token_id,
@@ -29,29 +42,99 @@ impl InitApi for Contract {
             config: Config {
                 cliff_duration,
                 vesting_duration,
+                token_decimals,
             },
             spare_balance: 0.into(),
-            pending_transfers: Default::default(),
+            audit_head: [0u8; 32],
+            audit_sequence: 0,
+            consumed_migration_batches: Default::default(),
+            migration_finalized: false,
+            paused_operations: Default::default(),
+            storage_deposits: Default::default(),
+            next_batch_id: 0,
+            last_error: Default::default(),
+            spare_staking_pool_id: None,
+            staked_spare_balance: 0.into(),
+            pending_spare_stake_amount: 0.into(),
+            spare_staking_locked: false,
+            buy_orders: IterableMap::new(StorageKey::BuyOrders),
+            next_buy_order_id: 0,
+            sell_orders: IterableMap::new(StorageKey::SellOrders),
+            next_sell_order_id: 0,
+            fee_schedule: None,
+            accrued_fees: 0.into(),
+            total_forfeited: 0.into(),
+            failed_transfers: Default::default(),
+            claim_keys: Default::default(),
+            multisig_approvers: Default::default(),
+            multisig_threshold: 0,
+            next_multisig_request_id: 0,
+            pending_actions: Default::default(),
+            role_expirations: Default::default(),
+            scoped_roles: Default::default(),
+            staking_exchange_rate: None,
         };
 
         Owner::init(&mut contract, &owner_id);
 
         contract
     }
+
+    fn migrate(&mut self, still_valid: Vec<Role>) -> u32 {
+        Self::require_owner();
+
+        self.revoke_orphaned_roles(&still_valid.into_iter().collect::<HashSet<_>>())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
     use near_sdk::AccountId;
     use near_sdk_contract_tools::owner::OwnerExternal;
     use rstest::rstest;
 
-    use crate::{init::InitApi, tests::fixtures::*, Contract};
+    use crate::{
+        auth::AuthApi, init::InitApi, tests::context::TestContext, tests::fixtures::*, Contract,
+        Role,
+    };
 
     #[rstest]
     fn init_assigns_owner(owner: AccountId, token: AccountId) {
-        let contract = Contract::new(token, 10, 20, owner.clone());
+        let contract = Contract::new(token, 10, 20, owner.clone(), 18);
 
         assert_eq!(contract.own_get_owner().unwrap(), owner);
     }
+
+    #[rstest]
+    fn migrate_revokes_roles_dropped_from_still_valid(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        issuer: AccountId,
+    ) {
+        context.switch_account(&owner);
+        assert!(contract.has_role(&issuer, Role::Issuer));
+
+        let revoked = contract.migrate(vec![Role::Executor, Role::Predecessor, Role::Custodian]);
+
+        assert_eq!(revoked, 1);
+        assert!(!contract.has_role(&issuer, Role::Issuer));
+    }
+
+    #[rstest]
+    fn migrate_requires_owner(mut context: TestContext, mut contract: Contract, alice: AccountId) {
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.migrate(vec![
+                Role::Issuer,
+                Role::Executor,
+                Role::Predecessor,
+                Role::Custodian,
+            ]);
+        }));
+
+        assert!(result.is_err());
+    }
 }
@@ -1,29 +1,42 @@
 pub mod auth;
+pub mod book;
 pub mod common;
 pub mod config;
+pub mod delegation;
 pub mod event;
 mod ft_receiver;
 pub mod grant;
 pub mod init;
+pub mod multisig;
+pub mod staking;
+pub mod storage;
 pub mod vesting;
 
 #[cfg(test)]
 pub mod testing_api;
+#[cfg(test)]
+pub mod tests;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use near_sdk::{
-    json_types::U128, near, store::IterableMap, AccountId, BorshStorageKey, PanicOnDefault,
+    json_types::U128, near, store::IterableMap, AccountId, BorshStorageKey, NearToken,
+    PanicOnDefault, PublicKey,
 };
 use near_sdk_contract_tools::{Owner, Pause, Rbac, Upgrade};
 
-pub use auth::Role;
+pub use auth::{Operation, Role};
+pub use book::{BuyOrder, SellOrder};
+pub use grant::{Condition, Schedule, TransferKey};
+pub use multisig::{Action, PendingAction};
 
 #[derive(BorshStorageKey)]
 #[near]
 pub(crate) enum StorageKey {
     Accounts,
     Pause,
+    BuyOrders,
+    SellOrders,
 }
 
 #[near(contract_state)]
@@ -36,7 +49,96 @@ pub struct Contract {
     pub accounts: IterableMap<AccountId, Account>,
     pub config: Config,
     pub spare_balance: U128,
-    pub pending_transfers: HashMap<AccountId, Vec<(u32, U128)>>,
+    /// Head of the append-only audit hash chain folding every emitted `LtipEvent`.
+    pub audit_head: [u8; 32],
+    /// Monotonically increasing index of the next event to be folded into `audit_head`.
+    pub audit_sequence: u64,
+    /// `batch_id`s from `FtMessage::Migrate` that have already been applied, guarding against replay.
+    pub consumed_migration_batches: HashSet<u64>,
+    /// Once set by the owner, no further `FtMessage::Migrate` batches are accepted.
+    pub migration_finalized: bool,
+    /// `Operation`s individually frozen by the owner via `pause_operation`, independent of the
+    /// contract-wide `Pause` switch.
+    pub paused_operations: HashMap<Operation, bool>,
+    /// NEAR deposited via `storage_deposit` on behalf of each account, drawn against to cover
+    /// the storage bytes their `Account`/`Grant` entries add to the contract.
+    pub storage_deposits: HashMap<AccountId, NearToken>,
+    /// Monotonically increasing id stamped onto every grant an `authorize` call locks, so a
+    /// stuck batch can be identified and reconciled without disturbing a newer one.
+    pub next_batch_id: u64,
+    /// The most recent reason `claim`/`buy`/`terminate` skipped an account without panicking,
+    /// keyed by that account. Cleared the next time the same account's batch entry succeeds.
+    pub last_error: HashMap<AccountId, String>,
+    /// The pool `stake_spare`/`unstake_spare` delegate the contract's idle `spare_balance` to.
+    /// Set by the first `stake_spare` call; further calls must target the same pool until
+    /// `staked_spare_balance` returns to zero.
+    pub spare_staking_pool_id: Option<AccountId>,
+    /// Amount of `spare_balance` currently delegated to `spare_staking_pool_id`, tracked
+    /// separately so `buy`/`authorize` accounting against `spare_balance` stays correct.
+    pub staked_spare_balance: U128,
+    /// Amount mid-flight in a `stake_spare`/`unstake_spare` promise, pending its callback's
+    /// outcome. Already deducted from `spare_balance` (or `staked_spare_balance`) at call time.
+    pub pending_spare_stake_amount: U128,
+    /// Set while a `stake_spare`/`unstake_spare` promise against `spare_staking_pool_id` is in
+    /// flight, guarding against a second call racing the first before its callback resolves.
+    pub spare_staking_locked: bool,
+    /// Resting buyback bids posted via `post_buy_order`, matched against grants' outstanding
+    /// `order_amount` by `match_buy_orders` in price-time priority.
+    pub buy_orders: IterableMap<u64, BuyOrder>,
+    /// Monotonically increasing id handed out to the next `post_buy_order` call.
+    pub next_buy_order_id: u64,
+    /// Resting sell orders posted via `place_sell_order`, each carving a specific amount out of
+    /// its grant's `order_amount` at a chosen `price_bps`, matched against `buy_orders` by
+    /// `match_sell_orders` in price-time priority.
+    pub sell_orders: IterableMap<u64, SellOrder>,
+    /// Monotonically increasing id handed out to the next `place_sell_order` call.
+    pub next_sell_order_id: u64,
+    /// The commission schedule `authorize` and `buy` apply, if any. `None` is the zero-fee
+    /// fast path: both flows pay/credit grantees in full and `accrued_fees` never grows.
+    pub fee_schedule: Option<FeeSchedule>,
+    /// Commission owed to `fee_schedule`'s `fee_collector`, credited eagerly by `authorize` and
+    /// `buy` as each fee is computed. Flushed to a real `ft_transfer` the next time `authorize`
+    /// dispatches a transfer batch (appended as one extra leg on that batch, settled by
+    /// `on_authorize_complete` exactly like the grantee legs), since `buy` itself never creates
+    /// a `Promise` to flush through.
+    pub accrued_fees: U128,
+    /// Running total of every `unvested_amount` `terminate`/`terminate_vesting` have ever
+    /// credited back into `spare_balance`, kept purely for audit/reporting — it never feeds
+    /// back into accounting the way `spare_balance` does.
+    pub total_forfeited: U128,
+    /// Grants whose `authorize` transfer most recently failed, keyed by `TransferKey`, mapped
+    /// to the amount that was restored to `order_amount` and the `authorized_bps` it failed at.
+    /// Populated by `on_authorize_complete` on failure, drained by `retry_failed` (or
+    /// overwritten the next time the same grant fails again).
+    pub failed_transfers: HashMap<TransferKey, (U128, u32)>,
+    /// Function-call access keys registered via `DelegationApi::register_claim_key`, each
+    /// scoped to only `claim` on the contract's own account, mapped to the grantee `claim`
+    /// should resolve to when invoked under that key instead of `env::predecessor_account_id`.
+    pub claim_keys: HashMap<PublicKey, AccountId>,
+    /// Accounts allowed to `MultisigApi::approve` a pending `Action`, set by `configure_multisig`.
+    pub multisig_approvers: HashSet<AccountId>,
+    /// Number of distinct `multisig_approvers` confirmations `MultisigApi::execute` requires.
+    pub multisig_threshold: u32,
+    /// Monotonically increasing id handed out to the next `propose_grant_role`/
+    /// `propose_revoke_role`/`propose_force_unpause` call.
+    pub next_multisig_request_id: u64,
+    /// Actions proposed via `MultisigApi` awaiting enough `approve` calls to `execute`.
+    pub pending_actions: HashMap<u64, PendingAction>,
+    /// Expiry (seconds since epoch) recorded by `AuthApi::grant_role_until` for a `(account_id,
+    /// role)` RBAC assignment. `has_role`/`require_role_active` treat an assignment as absent
+    /// once `now()` passes its recorded expiry; `sweep_expired` is what actually removes it.
+    pub role_expirations: HashMap<(AccountId, Role), u32>,
+    /// Scoped RBAC assignments granted via `AuthApi::grant_role_in_scope`, keyed by `(scope_id,
+    /// role, account_id)`. A global `Rbac` assignment is unaffected by (and not recorded in)
+    /// this set; `has_role_in_scope`/`members_in_scope` treat it as an implicit grant in every
+    /// scope instead.
+    pub scoped_roles: HashSet<(String, Role, AccountId)>,
+    /// yoctoNEAR owed per raw unit of the FT-denominated ledger amount `stake_idle`/
+    /// `stake_spare` mark as delegated, set by `StakingApi::set_staking_exchange_rate`. `None`
+    /// (the default) refuses every `stake_idle`/`stake_spare` call until the owner configures
+    /// one, since otherwise the attached NEAR and the ledger amount it locks out of `claim`/
+    /// `buy` have no defined relationship to each other.
+    pub staking_exchange_rate: Option<U128>,
 }
 
 #[near(serializers = [borsh, json])]
@@ -51,6 +153,42 @@ pub struct Grant {
     pub total_amount: U128,
     pub claimed_amount: U128,
     pub order_amount: U128,
+    pub terminated_at: Option<u32>,
+    pub schedule: Schedule,
+    /// Amount moved out of `order_amount` by an in-flight `authorize` batch, pending the
+    /// `ft_transfer` promise's outcome.
+    pub pending_amount: U128,
+    /// Set by `authorize` while this grant's `pending_amount` is in flight; skipped by `claim`,
+    /// `buy`, `terminate`, and `decline_orders` until `on_authorize_complete` clears it.
+    pub locked: bool,
+    /// The basis-point percentage `authorize` used to compute `pending_amount`, reported back
+    /// via `get_pending_transfers` while the grant is locked.
+    pub locked_bps: Option<u32>,
+    /// Minimum basis-point buyback price the grantee will accept for this grant's outstanding
+    /// order, set via `set_order_reserve`. `buy` skips (without touching `order_amount`) any
+    /// fill whose percentage is below this. `None` accepts any price.
+    pub min_buy_bps: Option<u32>,
+    /// The `authorize` batch that currently holds this grant's lock, if any. Lets
+    /// `reconcile_pending_transfer` target the exact batch it was asked to clean up rather than
+    /// a newer one that happens to have re-locked the same grant.
+    pub locked_batch_id: Option<u64>,
+    /// Block height at which this grant was locked, used to give `on_authorize_complete`'s
+    /// normal callback a fair chance to run before `reconcile_pending_transfer` steps in.
+    pub locked_at_block_height: Option<u64>,
+    /// Amount currently delegated to `staking_pool_id` via `stake_idle`.
+    pub staked_amount: U128,
+    /// Amount mid-flight in a `stake_idle`/`unstake` promise, pending its callback's outcome.
+    pub pending_stake_amount: U128,
+    /// Set while a `stake_idle`/`unstake` promise touching this grant is in flight; skipped by
+    /// `stake_idle` and `unstake` (but not `claim`/`buy`/`authorize`) until the matching
+    /// callback clears it.
+    pub staking_locked: bool,
+    /// The staking pool currently holding `staked_amount`, if any.
+    pub staking_pool_id: Option<AccountId>,
+    /// Release conditions not yet cleared by `apply_witness`. While non-empty, `claim` and
+    /// `buy` skip this grant (as if it were `locked`) even though its vesting curve may already
+    /// have unlocked funds.
+    pub conditions: Vec<Condition>,
 }
 
 #[near(serializers = [borsh, json])]
@@ -58,4 +196,25 @@ pub struct Grant {
 pub struct Config {
     pub cliff_duration: u32,
     pub vesting_duration: u32,
+    /// `token_id`'s NEP-148 `decimals`, supplied at construction since a cross-contract
+    /// `ft_metadata` call can't be awaited synchronously from `new`. Drives
+    /// `ConfigApi::to_token_units`, which callers use to convert a whole-token amount into the
+    /// raw on-chain units `issue`/`buy`/`authorize`/`FtMessage` actually expect.
+    pub token_decimals: u8,
+}
+
+/// Basis-point commission taken out of `authorize`/`buy`'s grantee-facing amounts and routed to
+/// `fee_collector`, set via `ConfigApi::set_fee_schedule`. Absent (the default) behaves as a
+/// zero-fee schedule: neither flow deducts or reports anything.
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct FeeSchedule {
+    /// Cut of each `buy` fill's bought-back amount credited to `accrued_fees` instead of the
+    /// grantee's `claimed_amount`.
+    pub buyback_bps: u16,
+    /// Cut of each `authorize` transfer credited to `accrued_fees` instead of the grantee's
+    /// `ft_transfer`.
+    pub authorize_bps: u16,
+    /// Account `accrued_fees` is paid out to whenever an `authorize` batch flushes it.
+    pub fee_collector: AccountId,
 }
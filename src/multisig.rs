@@ -0,0 +1,388 @@
+use std::collections::HashSet;
+
+use near_sdk::{env, near, require, AccountId};
+use near_sdk_contract_tools::owner::Owner;
+use near_sdk_contract_tools::pause::Pause;
+use near_sdk_contract_tools::rbac::Rbac;
+
+use crate::{event::LtipEvent, Contract, ContractExt, Role};
+
+/// A sensitive `AuthApi` mutation queued by `MultisigApi::propose_grant_role`/
+/// `propose_revoke_role`/`propose_force_unpause`, applied by `MultisigApi::execute` only once
+/// enough approvers have confirmed it.
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub enum Action {
+    GrantRole { account_id: AccountId, role: Role },
+    RevokeRole { account_id: AccountId, role: Role },
+    ForceUnpause,
+}
+
+/// A proposed `Action` together with the approvers who have confirmed it so far.
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct PendingAction {
+    pub action: Action,
+    pub approvals: HashSet<AccountId>,
+}
+
+/// MultisigApi layers an M-of-N approval flow on top of `AuthApi`'s sensitive operations (role
+/// assignment, clearing an incident pause), so a single compromised owner key can no longer
+/// unilaterally grant itself privilege or lift a pause. The owner still proposes an `Action`
+/// directly (nothing here changes who may *initiate* a request), but `execute` refuses to apply
+/// it until `multisig_threshold` of the configured `multisig_approvers` have called `approve`.
+/// Once `configure_multisig` sets a non-zero `multisig_threshold`, `AuthApi::grant_role`/
+/// `revoke_role`/`force_unpause`'s direct owner-gated shortcut is disabled (it panics) — this
+/// queue becomes the only way to apply those three actions.
+pub trait MultisigApi {
+    /// Sets the approver set and confirmation threshold. Replaces any prior configuration;
+    /// pending actions proposed under an old configuration are unaffected (their `approvals` are
+    /// still checked against whatever `multisig_threshold` is current at `execute` time).
+    fn configure_multisig(&mut self, approvers: Vec<AccountId>, threshold: u32);
+
+    /// Proposes granting `role` to `account_id`, returning the new pending request's id.
+    fn propose_grant_role(&mut self, account_id: AccountId, role: Role) -> u64;
+
+    /// Proposes revoking `role` from `account_id`, returning the new pending request's id.
+    fn propose_revoke_role(&mut self, account_id: AccountId, role: Role) -> u64;
+
+    /// Proposes clearing the contract-wide pause, returning the new pending request's id.
+    fn propose_force_unpause(&mut self) -> u64;
+
+    /// Records the caller's confirmation of `request_id`. The caller must be a configured
+    /// approver; confirming twice is a harmless no-op (`approvals` is a set).
+    fn approve(&mut self, request_id: u64);
+
+    /// Applies `request_id`'s action and removes it from the pending set, once its `approvals`
+    /// has reached `multisig_threshold`. Panics if the request doesn't exist or isn't yet
+    /// sufficiently confirmed.
+    fn execute(&mut self, request_id: u64);
+
+    /// Returns a pending request's action and current approvals, if it still exists.
+    fn get_pending_action(&self, request_id: u64) -> Option<PendingAction>;
+}
+
+#[near]
+impl MultisigApi for Contract {
+    fn configure_multisig(&mut self, approvers: Vec<AccountId>, threshold: u32) {
+        Self::require_owner();
+
+        require!(
+            threshold > 0 && (threshold as usize) <= approvers.len(),
+            "threshold must be between 1 and the number of approvers"
+        );
+
+        self.multisig_approvers = approvers.into_iter().collect();
+        self.multisig_threshold = threshold;
+    }
+
+    fn propose_grant_role(&mut self, account_id: AccountId, role: Role) -> u64 {
+        Self::require_owner();
+
+        self.propose(Action::GrantRole { account_id, role })
+    }
+
+    fn propose_revoke_role(&mut self, account_id: AccountId, role: Role) -> u64 {
+        Self::require_owner();
+
+        self.propose(Action::RevokeRole { account_id, role })
+    }
+
+    fn propose_force_unpause(&mut self) -> u64 {
+        Self::require_owner();
+
+        self.propose(Action::ForceUnpause)
+    }
+
+    fn approve(&mut self, request_id: u64) {
+        let caller = env::predecessor_account_id();
+        require!(
+            self.multisig_approvers.contains(&caller),
+            "Only a configured approver may confirm a pending action"
+        );
+
+        let pending = self
+            .pending_actions
+            .get_mut(&request_id)
+            .unwrap_or_else(|| env::panic_str("No pending action for this request id"));
+        pending.approvals.insert(caller);
+    }
+
+    fn execute(&mut self, request_id: u64) {
+        let pending = self
+            .pending_actions
+            .get(&request_id)
+            .unwrap_or_else(|| env::panic_str("No pending action for this request id"));
+        require!(
+            pending.approvals.len() as u32 >= self.multisig_threshold,
+            "Not enough approvals to execute this action yet"
+        );
+
+        let action = pending.action.clone();
+        self.pending_actions.remove(&request_id);
+
+        match action {
+            Action::GrantRole { account_id, role } => {
+                self.add_role(&account_id, &role);
+                self.commit_event(&LtipEvent::RoleGranted((account_id, role)));
+            }
+            Action::RevokeRole { account_id, role } => {
+                self.remove_role(&account_id, &role);
+                self.commit_event(&LtipEvent::RoleRevoked((account_id, role)));
+            }
+            Action::ForceUnpause => {
+                self.unpause();
+                self.commit_event(&LtipEvent::ForceUnpaused(env::predecessor_account_id()));
+            }
+        }
+    }
+
+    fn get_pending_action(&self, request_id: u64) -> Option<PendingAction> {
+        self.pending_actions.get(&request_id).cloned()
+    }
+}
+
+impl Contract {
+    fn propose(&mut self, action: Action) -> u64 {
+        require!(
+            self.multisig_threshold > 0,
+            "Multisig has not been configured"
+        );
+
+        self.next_multisig_request_id += 1;
+        let request_id = self.next_multisig_request_id;
+        self.pending_actions.insert(
+            request_id,
+            PendingAction {
+                action,
+                approvals: HashSet::new(),
+            },
+        );
+
+        request_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::AccountId;
+    use rstest::*;
+
+    use crate::{
+        auth::AuthApi, event::AuditApi, multisig::MultisigApi, tests::context::TestContext,
+        tests::fixtures::*, Contract, Role,
+    };
+
+    #[rstest]
+    fn execute_applies_the_action_once_threshold_is_met(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone(), bob.clone()], 2);
+        let request_id = contract.propose_grant_role(alice.clone(), Role::Executor);
+
+        context.switch_account(&alice);
+        contract.approve(request_id);
+
+        context.switch_account(&bob);
+        contract.approve(request_id);
+
+        contract.execute(request_id);
+
+        assert!(contract.has_role(&alice, Role::Executor));
+        assert!(contract.get_pending_action(request_id).is_none());
+    }
+
+    #[rstest]
+    fn execute_rejects_insufficient_approvals(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone(), bob.clone()], 2);
+        let request_id = contract.propose_grant_role(alice.clone(), Role::Executor);
+
+        context.switch_account(&alice);
+        contract.approve(request_id);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.execute(request_id);
+        }));
+
+        assert!(result.is_err());
+        assert!(!contract.has_role(&alice, Role::Executor));
+    }
+
+    #[rstest]
+    fn approve_requires_a_configured_approver(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+        bob: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone()], 1);
+        let request_id = contract.propose_grant_role(alice.clone(), Role::Executor);
+
+        context.switch_account(&bob);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.approve(request_id);
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn propose_grant_role_requires_owner(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone()], 1);
+
+        context.switch_account(&alice);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.propose_grant_role(alice.clone(), Role::Executor);
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn execute_commits_an_audit_event(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone()], 1);
+        let request_id = contract.propose_grant_role(alice.clone(), Role::Executor);
+        let (initial_sequence, _) = contract.get_audit_head();
+
+        context.switch_account(&alice);
+        contract.approve(request_id);
+        contract.execute(request_id);
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, initial_sequence + 1);
+    }
+
+    #[rstest]
+    fn grant_role_is_disabled_once_multisig_is_configured(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone()], 1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.grant_role(&alice, Role::Executor);
+        }));
+
+        assert!(result.is_err());
+        assert!(!contract.has_role(&alice, Role::Executor));
+    }
+
+    #[rstest]
+    fn force_unpause_is_disabled_once_multisig_is_configured(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone()], 1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.force_unpause();
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn grant_role_until_is_disabled_once_multisig_is_configured(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone()], 1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.grant_role_until(&alice, Role::Executor, u32::MAX);
+        }));
+
+        assert!(result.is_err());
+        assert!(!contract.has_role(&alice, Role::Executor));
+    }
+
+    #[rstest]
+    fn grant_role_in_scope_is_disabled_once_multisig_is_configured(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract.configure_multisig(vec![alice.clone()], 1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.grant_role_in_scope(&alice, Role::Executor, "program-a".to_string());
+        }));
+
+        assert!(result.is_err());
+        assert!(!contract.has_role_in_scope(&alice, Role::Executor, "program-a".to_string()));
+    }
+
+    #[rstest]
+    fn revoke_role_in_scope_is_disabled_once_multisig_is_configured(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+        alice: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        context.switch_account(&owner);
+        contract
+            .scoped_roles
+            .insert(("program-a".to_string(), Role::Executor, alice.clone()));
+        contract.configure_multisig(vec![alice.clone()], 1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.revoke_role_in_scope(&alice, Role::Executor, "program-a".to_string());
+        }));
+
+        assert!(result.is_err());
+        assert!(contract.has_role_in_scope(&alice, Role::Executor, "program-a".to_string()));
+    }
+}
@@ -0,0 +1,1155 @@
+use near_sdk::{
+    env, env::log_str, json_types::U128, near, require, serde_json, AccountId, Gas, NearToken,
+    Promise, PromiseResult,
+};
+use near_sdk_contract_tools::rbac::Rbac;
+
+use crate::{event::LtipEvent, grant::TransferKey, Contract, ContractExt, Role};
+
+const GAS_FOR_STAKE_CALL: Gas = Gas::from_tgas(50);
+const GAS_FOR_STAKE_CALLBACK: Gas = Gas::from_tgas(10);
+
+/// StakingApi lets the executor delegate the contract's idle native NEAR balance to a staking
+/// pool instead of letting it sit unused, mirroring the lockup contract's staking-pool
+/// integration (deposit, withdraw, and reconcile on callback failure). `token_id`'s balance (the
+/// vested grant/`spare_balance` ledger) is an NEP-141 FT and can never be attached as a deposit
+/// to a pool's `deposit_and_stake` — unlike the lockup contract, which stakes its own native
+/// NEAR. `stake_idle`/`stake_spare` are therefore `#[payable]`, but the attached deposit isn't
+/// trusted at face value: it must equal the FT-ledger amount being locked out of `claim`/`buy`
+/// times `Contract::staking_exchange_rate`, the owner-configured yoctoNEAR-per-unit rate set via
+/// `set_staking_exchange_rate`. Without that check, an executor could lock an arbitrary ledger
+/// amount out of claimability while attaching NEAR wildly out of proportion to it.
+/// `Grant::staked_amount`/`staked_spare_balance` remain an FT-unit bookkeeping ledger recording
+/// how much of the vested/spare balance each delegation corresponds to; they track no native
+/// NEAR themselves and back nothing by construction.
+pub trait StakingApi {
+    /// Sets the yoctoNEAR owed per raw unit of FT-ledger amount that `stake_idle`/`stake_spare`
+    /// require the caller to attach, e.g. from an oracle-backed NEAR/token price. Owner-gated,
+    /// same as `set_fee_schedule`. Must be non-zero: a zero rate would let `stake_idle`/
+    /// `stake_spare` lock ledger balance out of `claim`/`buy` for free.
+    fn set_staking_exchange_rate(&mut self, staking_exchange_rate: U128);
+
+    /// Returns the currently configured staking exchange rate, if any.
+    fn get_staking_exchange_rate(&self) -> Option<U128>;
+
+    /// Forwards the attached deposit to `pool_id`'s `deposit_and_stake` in one call covering the
+    /// idle portion (vested, not yet claimed/ordered/staked) of each of `account_ids`' grants.
+    /// Locks each touched grant (`Grant::staking_locked`) until `on_stake_idle_complete`
+    /// resolves. Requires the attached deposit to equal the combined idle amount times
+    /// `staking_exchange_rate`, and that a rate has been configured at all.
+    fn stake_idle(&mut self, pool_id: AccountId, account_ids: Vec<AccountId>);
+
+    /// Callback invoked after `deposit_and_stake` resolves: credits `staked_amount` on success,
+    /// or reverts the lock (leaving the amount unstaked) on failure, recording failures via
+    /// `LtipEvent::StakeReconciled`.
+    fn on_stake_idle_complete(&mut self, transfer_keys: Vec<TransferKey>);
+
+    /// Recalls `staked_amount` from `pool_id` for each of `account_ids`' grants currently
+    /// delegated to it. Locks each touched grant until `on_unstake_complete` resolves.
+    fn unstake(&mut self, pool_id: AccountId, account_ids: Vec<AccountId>);
+
+    /// Callback invoked after `withdraw` resolves: clears `staked_amount` on success, or leaves
+    /// it in place (still delegated) on failure, recording failures via
+    /// `LtipEvent::StakeReconciled`.
+    fn on_unstake_complete(&mut self, transfer_keys: Vec<TransferKey>);
+
+    /// Returns the amount currently delegated to a staking pool for a single grant.
+    fn get_staked_amount(&self, account_id: AccountId, issue_at: u32) -> U128;
+
+    /// Forwards the attached deposit to `pool_id`'s `deposit_and_stake`, recording `amount` of
+    /// the contract's idle `spare_balance` as the FT-unit ledger entry this delegation
+    /// corresponds to (it earns no yield itself — an NEP-141 balance can't be staked; only the
+    /// attached native NEAR actually is). Deducts `amount` from `spare_balance` up front and
+    /// locks further `stake_spare`/`unstake_spare` calls until `on_stake_spare_complete`
+    /// resolves, guarding against staking more than what's actually free to delegate. The first
+    /// call fixes `spare_staking_pool_id`; later calls must target that same pool until it's
+    /// fully unstaked. Requires the attached deposit to equal `amount` times
+    /// `staking_exchange_rate`, and that a rate has been configured at all.
+    fn stake_spare(&mut self, pool_id: AccountId, amount: U128);
+
+    /// Callback invoked after `stake_spare`'s `deposit_and_stake` resolves: credits
+    /// `staked_spare_balance` on success, or restores `spare_balance` on failure, recording
+    /// failures via `LtipEvent::SpareStakeReconciled`.
+    fn on_stake_spare_complete(&mut self);
+
+    /// Recalls `amount` of `staked_spare_balance` from `spare_staking_pool_id` via `withdraw`.
+    /// Deducts `amount` from `staked_spare_balance` up front and locks further `stake_spare`/
+    /// `unstake_spare` calls until `on_unstake_spare_complete` resolves.
+    fn unstake_spare(&mut self, amount: U128);
+
+    /// Callback invoked after `unstake_spare`'s `withdraw` resolves: credits `spare_balance` on
+    /// success, or restores `staked_spare_balance` on failure, recording failures via
+    /// `LtipEvent::SpareStakeReconciled`.
+    fn on_unstake_spare_complete(&mut self);
+
+    /// Returns the portion of `spare_balance` that is free (not delegated, not mid-flight in a
+    /// `stake_spare`/`unstake_spare` call) and could be handed to `stake_spare` right now.
+    fn get_free_spare_balance(&self) -> U128;
+
+    /// Returns the portion of `spare_balance` currently delegated to `spare_staking_pool_id`.
+    fn get_staked_spare_balance(&self) -> U128;
+
+    /// Queries `spare_staking_pool_id`'s `get_account_staked_balance` for this contract and
+    /// reconciles `staked_spare_balance` against the pool's own figure, picking up reward drift
+    /// that `stake_spare`/`unstake_spare` don't otherwise account for (they only ever move the
+    /// exact amount requested). Locks further `stake_spare`/`unstake_spare` calls until
+    /// `on_sync_staked_spare_balance` resolves.
+    fn sync_staked_spare_balance(&mut self);
+
+    /// Callback invoked after `sync_staked_spare_balance`'s view call resolves: overwrites
+    /// `staked_spare_balance` with the pool-reported figure on success, leaves it untouched on
+    /// failure.
+    fn on_sync_staked_spare_balance(&mut self);
+}
+
+#[near]
+impl StakingApi for Contract {
+    fn set_staking_exchange_rate(&mut self, staking_exchange_rate: U128) {
+        Self::require_owner();
+        require!(
+            staking_exchange_rate.0 > 0,
+            "staking_exchange_rate must be non-zero"
+        );
+
+        self.staking_exchange_rate = Some(staking_exchange_rate);
+    }
+
+    fn get_staking_exchange_rate(&self) -> Option<U128> {
+        self.staking_exchange_rate
+    }
+
+    #[payable]
+    fn stake_idle(&mut self, pool_id: AccountId, account_ids: Vec<AccountId>) {
+        self.require_role_active(&Role::Executor);
+
+        let mut candidates: Vec<(TransferKey, u128)> = Vec::new();
+
+        for account_id in account_ids {
+            if let Some(account) = self.accounts.get(&account_id) {
+                for (issue_at, grant) in account.grants.iter() {
+                    if grant.staking_locked {
+                        continue;
+                    }
+                    if let Some(existing_pool) = &grant.staking_pool_id {
+                        if existing_pool != &pool_id {
+                            continue;
+                        }
+                    }
+
+                    let committed = grant.claimed_amount.0
+                        + grant.order_amount.0
+                        + grant.pending_amount.0
+                        + grant.staked_amount.0;
+                    let idle_amount = grant.get_vested_amount().saturating_sub(committed);
+                    if idle_amount == 0 {
+                        continue;
+                    }
+
+                    candidates.push((
+                        TransferKey {
+                            account_id: account_id.clone(),
+                            issue_at: *issue_at,
+                        },
+                        idle_amount,
+                    ));
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            return;
+        }
+
+        let total_idle_amount: u128 = candidates.iter().map(|(_, idle_amount)| idle_amount).sum();
+        self.require_exact_stake_deposit(total_idle_amount);
+
+        let mut transfer_keys = Vec::with_capacity(candidates.len());
+        for (transfer_key, idle_amount) in candidates {
+            let account = self
+                .accounts
+                .get_mut(&transfer_key.account_id)
+                .unwrap_or_else(|| env::panic_str("Account vanished mid-batch"));
+            let grant = account
+                .grants
+                .get_mut(&transfer_key.issue_at)
+                .unwrap_or_else(|| env::panic_str("Grant vanished mid-batch"));
+
+            grant.staking_locked = true;
+            grant.pending_stake_amount = U128::from(idle_amount);
+            grant.staking_pool_id = Some(pool_id.clone());
+
+            transfer_keys.push(transfer_key);
+        }
+
+        self.request_stake_call(
+            pool_id,
+            "deposit_and_stake",
+            Vec::new(),
+            env::attached_deposit().as_yoctonear(),
+            transfer_keys,
+            "on_stake_idle_complete",
+        );
+    }
+
+    #[private]
+    fn on_stake_idle_complete(&mut self, transfer_keys: Vec<TransferKey>) {
+        self.settle_stake_callback(transfer_keys, true);
+    }
+
+    fn unstake(&mut self, pool_id: AccountId, account_ids: Vec<AccountId>) {
+        self.require_role_active(&Role::Executor);
+
+        let mut transfer_keys = Vec::new();
+        let mut total_amount: u128 = 0;
+
+        for account_id in account_ids {
+            if let Some(account) = self.accounts.get_mut(&account_id) {
+                for (issue_at, grant) in account.grants.iter_mut() {
+                    if grant.staking_locked || grant.staked_amount.0 == 0 {
+                        continue;
+                    }
+                    if grant.staking_pool_id.as_ref() != Some(&pool_id) {
+                        continue;
+                    }
+
+                    grant.staking_locked = true;
+                    grant.pending_stake_amount = grant.staked_amount;
+
+                    total_amount += grant.staked_amount.0;
+                    transfer_keys.push(TransferKey {
+                        account_id: account_id.clone(),
+                        issue_at: *issue_at,
+                    });
+                }
+            }
+        }
+
+        if transfer_keys.is_empty() {
+            return;
+        }
+
+        let args =
+            serde_json::to_vec(&serde_json::json!({ "amount": U128::from(total_amount) })).unwrap();
+        self.request_stake_call(
+            pool_id,
+            "withdraw",
+            args,
+            0,
+            transfer_keys,
+            "on_unstake_complete",
+        );
+    }
+
+    #[private]
+    fn on_unstake_complete(&mut self, transfer_keys: Vec<TransferKey>) {
+        self.settle_stake_callback(transfer_keys, false);
+    }
+
+    fn get_staked_amount(&self, account_id: AccountId, issue_at: u32) -> U128 {
+        self.accounts
+            .get(&account_id)
+            .and_then(|account| account.grants.get(&issue_at))
+            .map(|grant| grant.staked_amount)
+            .unwrap_or(U128::from(0))
+    }
+
+    #[payable]
+    fn stake_spare(&mut self, pool_id: AccountId, amount: U128) {
+        self.require_role_active(&Role::Executor);
+        require!(
+            !self.spare_staking_locked,
+            "A spare-balance stake/unstake operation is already in flight"
+        );
+        if let Some(existing_pool) = &self.spare_staking_pool_id {
+            require!(
+                existing_pool == &pool_id,
+                "spare_balance is already delegated to a different staking pool"
+            );
+        }
+        require!(amount.0 > 0, "Amount must be non-zero");
+        require!(
+            amount.0 <= self.spare_balance.0,
+            "Amount exceeds the free portion of spare_balance"
+        );
+        self.require_exact_stake_deposit(amount.0);
+
+        self.spare_balance.0 -= amount.0;
+        self.pending_spare_stake_amount = amount;
+        self.spare_staking_locked = true;
+        self.spare_staking_pool_id = Some(pool_id.clone());
+
+        Promise::new(pool_id)
+            .function_call(
+                "deposit_and_stake".to_string(),
+                Vec::new(),
+                env::attached_deposit(),
+                GAS_FOR_STAKE_CALL,
+            )
+            .then(Promise::new(env::current_account_id()).function_call(
+                "on_stake_spare_complete".to_string(),
+                Vec::new(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_STAKE_CALLBACK,
+            ));
+    }
+
+    #[private]
+    fn on_stake_spare_complete(&mut self) {
+        self.settle_spare_stake_callback(true);
+    }
+
+    fn unstake_spare(&mut self, amount: U128) {
+        self.require_role_active(&Role::Executor);
+        require!(
+            !self.spare_staking_locked,
+            "A spare-balance stake/unstake operation is already in flight"
+        );
+        let pool_id = self
+            .spare_staking_pool_id
+            .clone()
+            .unwrap_or_else(|| env::panic_str("spare_balance has no staking pool set"));
+        require!(amount.0 > 0, "Amount must be non-zero");
+        require!(
+            amount.0 <= self.staked_spare_balance.0,
+            "Amount exceeds staked_spare_balance"
+        );
+
+        self.staked_spare_balance.0 -= amount.0;
+        self.pending_spare_stake_amount = amount;
+        self.spare_staking_locked = true;
+
+        Promise::new(pool_id)
+            .function_call(
+                "withdraw".to_string(),
+                serde_json::to_vec(&serde_json::json!({ "amount": amount })).unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_STAKE_CALL,
+            )
+            .then(Promise::new(env::current_account_id()).function_call(
+                "on_unstake_spare_complete".to_string(),
+                Vec::new(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_STAKE_CALLBACK,
+            ));
+    }
+
+    #[private]
+    fn on_unstake_spare_complete(&mut self) {
+        self.settle_spare_stake_callback(false);
+    }
+
+    fn get_free_spare_balance(&self) -> U128 {
+        self.spare_balance
+    }
+
+    fn get_staked_spare_balance(&self) -> U128 {
+        self.staked_spare_balance
+    }
+
+    fn sync_staked_spare_balance(&mut self) {
+        self.require_role_active(&Role::Executor);
+        require!(
+            !self.spare_staking_locked,
+            "A spare-balance stake/unstake operation is already in flight"
+        );
+        let pool_id = self
+            .spare_staking_pool_id
+            .clone()
+            .unwrap_or_else(|| env::panic_str("spare_balance has no staking pool set"));
+
+        self.spare_staking_locked = true;
+
+        Promise::new(pool_id)
+            .function_call(
+                "get_account_staked_balance".to_string(),
+                serde_json::to_vec(&serde_json::json!({ "account_id": env::current_account_id() }))
+                    .unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_STAKE_CALL,
+            )
+            .then(Promise::new(env::current_account_id()).function_call(
+                "on_sync_staked_spare_balance".to_string(),
+                Vec::new(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_STAKE_CALLBACK,
+            ));
+    }
+
+    #[private]
+    fn on_sync_staked_spare_balance(&mut self) {
+        self.spare_staking_locked = false;
+
+        if let PromiseResult::Successful(value) = env::promise_result(0) {
+            if let Ok(actual_balance) = serde_json::from_slice::<U128>(&value) {
+                self.staked_spare_balance = actual_balance;
+            }
+        }
+    }
+}
+
+impl Contract {
+    /// Panics unless `env::attached_deposit()` equals `ft_amount` times the configured
+    /// `staking_exchange_rate`, or if no rate has been configured at all. Shared by `stake_idle`
+    /// (`ft_amount` the combined idle amount across the batch) and `stake_spare` (`ft_amount`
+    /// the caller-supplied amount), so neither can lock ledger balance out of `claim`/`buy` for
+    /// an attached deposit disproportionate to it.
+    fn require_exact_stake_deposit(&self, ft_amount: u128) {
+        let rate = self
+            .staking_exchange_rate
+            .unwrap_or_else(|| env::panic_str("No staking exchange rate has been configured"));
+
+        let required_deposit = ft_amount
+            .checked_mul(rate.0)
+            .unwrap_or_else(|| env::panic_str("Required stake deposit overflowed u128"));
+
+        require!(
+            env::attached_deposit() == NearToken::from_yoctonear(required_deposit),
+            "Attached deposit must equal the ledger amount times the configured staking exchange rate"
+        );
+    }
+
+    /// Fires a single cross-contract call against `pool_id` covering every grant in
+    /// `transfer_keys` (already locked with `pending_stake_amount` set by the caller), then
+    /// chains `callback_method` to reconcile the result.
+    pub(crate) fn request_stake_call(
+        &self,
+        pool_id: AccountId,
+        method_name: &str,
+        args: Vec<u8>,
+        attached_amount: u128,
+        transfer_keys: Vec<TransferKey>,
+        callback_method: &str,
+    ) {
+        require!(!transfer_keys.is_empty(), "No grants to act on");
+
+        Promise::new(pool_id)
+            .function_call(
+                method_name.to_string(),
+                args,
+                NearToken::from_yoctonear(attached_amount),
+                GAS_FOR_STAKE_CALL,
+            )
+            .then(Promise::new(env::current_account_id()).function_call(
+                callback_method.to_string(),
+                serde_json::to_vec(&serde_json::json!({ "transfer_keys": transfer_keys })).unwrap(),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_STAKE_CALLBACK,
+            ));
+    }
+
+    /// Shared reconciliation for `on_stake_idle_complete`/`on_unstake_complete`: on success,
+    /// moves `pending_stake_amount` into (`staking`) or out of (`unstaking`) `staked_amount`; on
+    /// failure, the grant is unlocked without moving anything and reported via
+    /// `LtipEvent::StakeReconciled` for operator follow-up.
+    fn settle_stake_callback(&mut self, transfer_keys: Vec<TransferKey>, staking: bool) {
+        let succeeded = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        let mut reconciled = Vec::new();
+
+        for transfer_key in transfer_keys {
+            let Some(account) = self.accounts.get_mut(&transfer_key.account_id) else {
+                continue;
+            };
+            let Some(grant) = account.grants.get_mut(&transfer_key.issue_at) else {
+                continue;
+            };
+
+            let pending = grant.pending_stake_amount.0;
+
+            if succeeded {
+                if staking {
+                    grant.staked_amount.0 += pending;
+                } else {
+                    grant.staked_amount.0 = grant.staked_amount.0.saturating_sub(pending);
+                    if grant.staked_amount.0 == 0 {
+                        grant.staking_pool_id = None;
+                    }
+                }
+            } else {
+                log_str(&format!(
+                    "Staking call failed for {} at issue date {}, grant left unlocked with no balance change",
+                    transfer_key.account_id, transfer_key.issue_at
+                ));
+                reconciled.push((
+                    transfer_key.account_id.clone(),
+                    transfer_key.issue_at,
+                    pending,
+                ));
+            }
+
+            grant.pending_stake_amount = U128::from(0);
+            grant.staking_locked = false;
+        }
+
+        if !reconciled.is_empty() {
+            self.commit_event(&LtipEvent::StakeReconciled(reconciled));
+        }
+    }
+
+    /// Shared reconciliation for `on_stake_spare_complete`/`on_unstake_spare_complete`: on
+    /// success, moves `pending_spare_stake_amount` into (`staking`) or out of (`unstaking`)
+    /// `staked_spare_balance`; on failure, the amount is restored to where it was deducted from
+    /// and reported via `LtipEvent::SpareStakeReconciled` for operator follow-up.
+    fn settle_spare_stake_callback(&mut self, staking: bool) {
+        let succeeded = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        let pending = self.pending_spare_stake_amount.0;
+
+        if succeeded {
+            if staking {
+                self.staked_spare_balance.0 += pending;
+            } else {
+                self.spare_balance.0 += pending;
+                if self.staked_spare_balance.0 == 0 {
+                    self.spare_staking_pool_id = None;
+                }
+            }
+        } else {
+            log_str(&format!(
+                "Spare-balance {} call failed, restoring {} to its prior balance",
+                if staking {
+                    "stake_spare"
+                } else {
+                    "unstake_spare"
+                },
+                pending
+            ));
+            if staking {
+                self.spare_balance.0 += pending;
+            } else {
+                self.staked_spare_balance.0 += pending;
+            }
+            self.commit_event(&LtipEvent::SpareStakeReconciled((staking, pending)));
+        }
+
+        self.pending_spare_stake_amount = U128::from(0);
+        self.spare_staking_locked = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{json_types::U128, serde_json, AccountId, NearToken, PromiseResult};
+    use rstest::*;
+
+    use crate::{
+        event::AuditApi,
+        grant::{GrantApi, TransferKey},
+        staking::StakingApi,
+        testing_api::DEFAULT_CLIFF,
+        tests::context::TestContext,
+        tests::fixtures::*,
+        Contract,
+    };
+
+    fn pool() -> AccountId {
+        "pool.poolv1.near".parse().unwrap()
+    }
+
+    #[rstest]
+    fn owner_can_set_staking_exchange_rate(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+    ) {
+        context.switch_account(&owner);
+        contract.set_staking_exchange_rate(U128::from(42));
+
+        assert_eq!(contract.get_staking_exchange_rate(), Some(U128::from(42)));
+    }
+
+    #[rstest]
+    fn set_staking_exchange_rate_requires_owner(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        context.switch_account(&alice);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_staking_exchange_rate(U128::from(42));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(contract.get_staking_exchange_rate(), None);
+    }
+
+    #[rstest]
+    fn set_staking_exchange_rate_rejects_zero(
+        mut context: TestContext,
+        mut contract: Contract,
+        owner: AccountId,
+    ) {
+        context.switch_account(&owner);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.set_staking_exchange_rate(U128::from(0));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(contract.get_staking_exchange_rate(), None);
+    }
+
+    #[rstest]
+    fn stake_idle_locks_and_computes_idle_amount(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.claimed_amount = U128::from(300);
+        }
+
+        contract.staking_exchange_rate = Some(U128::from(1));
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(4_000);
+        context.with_attached_deposit(NearToken::from_yoctonear(700), || {
+            contract.stake_idle(pool(), vec![alice.clone()]);
+        });
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert!(grant.staking_locked);
+        assert_eq!(grant.pending_stake_amount.0, 700);
+        assert_eq!(grant.staking_pool_id, Some(pool()));
+        assert_eq!(grant.staked_amount.0, 0);
+    }
+
+    #[rstest]
+    fn stake_idle_requires_executor_role(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        context.switch_account(&alice);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.stake_idle(pool(), vec![alice.clone()]);
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn stake_idle_requires_a_configured_exchange_rate(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(4_000);
+        let result = context.with_attached_deposit(NearToken::from_yoctonear(700), || {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                contract.stake_idle(pool(), vec![alice.clone()]);
+            }))
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn stake_idle_rejects_a_deposit_not_matching_the_rate(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        contract.staking_exchange_rate = Some(U128::from(1));
+
+        context.switch_to_executor();
+        context.set_block_timestamp_in_seconds(4_000);
+        let result = context.with_attached_deposit(NearToken::from_yoctonear(1), || {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                contract.stake_idle(pool(), vec![alice.clone()]);
+            }))
+        });
+
+        assert!(result.is_err());
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert!(!grant.staking_locked);
+    }
+
+    #[rstest]
+    fn on_stake_idle_complete_credits_staked_amount_on_success(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.staking_locked = true;
+            grant.pending_stake_amount = U128::from(700);
+            grant.staking_pool_id = Some(pool());
+        }
+
+        context.set_promise_results(vec![PromiseResult::Successful(vec![])]);
+        contract.on_stake_idle_complete(vec![TransferKey {
+            account_id: alice.clone(),
+            issue_at: DEFAULT_CLIFF,
+        }]);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert!(!grant.staking_locked);
+        assert_eq!(grant.pending_stake_amount.0, 0);
+        assert_eq!(grant.staked_amount.0, 700);
+    }
+
+    #[rstest]
+    fn on_stake_idle_complete_reverts_lock_on_failure(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.staking_locked = true;
+            grant.pending_stake_amount = U128::from(700);
+            grant.staking_pool_id = Some(pool());
+        }
+
+        context.set_promise_results(vec![PromiseResult::Failed]);
+        contract.on_stake_idle_complete(vec![TransferKey {
+            account_id: alice.clone(),
+            issue_at: DEFAULT_CLIFF,
+        }]);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert!(!grant.staking_locked);
+        assert_eq!(grant.pending_stake_amount.0, 0);
+        assert_eq!(grant.staked_amount.0, 0);
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, 1);
+    }
+
+    #[rstest]
+    fn unstake_locks_and_withdraws_on_success(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.staked_amount = U128::from(700);
+            grant.staking_pool_id = Some(pool());
+        }
+
+        context.switch_to_executor();
+        contract.unstake(pool(), vec![alice.clone()]);
+
+        {
+            let grant = contract
+                .accounts
+                .get(&alice)
+                .unwrap()
+                .grants
+                .get(&DEFAULT_CLIFF)
+                .unwrap();
+            assert!(grant.staking_locked);
+            assert_eq!(grant.pending_stake_amount.0, 700);
+        }
+
+        context.set_promise_results(vec![PromiseResult::Successful(vec![])]);
+        contract.on_unstake_complete(vec![TransferKey {
+            account_id: alice.clone(),
+            issue_at: DEFAULT_CLIFF,
+        }]);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert!(!grant.staking_locked);
+        assert_eq!(grant.staked_amount.0, 0);
+        assert_eq!(grant.staking_pool_id, None);
+    }
+
+    #[rstest]
+    fn unstake_skips_grants_already_locked(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.staked_amount = U128::from(700);
+            grant.staking_pool_id = Some(pool());
+            grant.staking_locked = true;
+        }
+
+        context.switch_to_executor();
+        contract.unstake(pool(), vec![alice.clone()]);
+
+        assert_eq!(contract.get_staked_amount(alice, DEFAULT_CLIFF).0, 700);
+    }
+
+    #[rstest]
+    fn terminate_recalls_staked_funds(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.staked_amount = U128::from(400);
+            grant.staking_pool_id = Some(pool());
+        }
+
+        context.switch_to_executor();
+        contract.terminate(alice.clone(), DEFAULT_CLIFF + 500);
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert!(grant.staking_locked);
+        assert_eq!(grant.pending_stake_amount.0, 400);
+        assert_eq!(grant.staked_amount.0, 400);
+    }
+
+    #[rstest]
+    fn stake_spare_locks_and_deducts_from_spare_balance(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = U128::from(1_000);
+        contract.staking_exchange_rate = Some(U128::from(1));
+
+        context.switch_to_executor();
+        context.with_attached_deposit(NearToken::from_yoctonear(600), || {
+            contract.stake_spare(pool(), U128::from(600));
+        });
+
+        assert!(contract.spare_staking_locked);
+        assert_eq!(contract.spare_balance.0, 400);
+        assert_eq!(contract.pending_spare_stake_amount.0, 600);
+        assert_eq!(contract.spare_staking_pool_id, Some(pool()));
+        assert_eq!(contract.staked_spare_balance.0, 0);
+    }
+
+    #[rstest]
+    fn stake_spare_refuses_to_exceed_free_balance(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = U128::from(100);
+
+        context.switch_to_executor();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.stake_spare(pool(), U128::from(600));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(contract.spare_balance.0, 100);
+    }
+
+    #[rstest]
+    fn stake_spare_requires_executor_role(mut context: TestContext, mut contract: Contract) {
+        contract.spare_balance = U128::from(1_000);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.stake_spare(pool(), U128::from(600));
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn stake_spare_requires_a_configured_exchange_rate(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = U128::from(1_000);
+
+        context.switch_to_executor();
+        let result = context.with_attached_deposit(NearToken::from_yoctonear(600), || {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                contract.stake_spare(pool(), U128::from(600));
+            }))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(contract.spare_balance.0, 1_000);
+    }
+
+    #[rstest]
+    fn stake_spare_rejects_a_deposit_not_matching_the_rate(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = U128::from(1_000);
+        contract.staking_exchange_rate = Some(U128::from(1));
+
+        context.switch_to_executor();
+        let result = context.with_attached_deposit(NearToken::from_yoctonear(1), || {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                contract.stake_spare(pool(), U128::from(600));
+            }))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(contract.spare_balance.0, 1_000);
+    }
+
+    #[rstest]
+    fn on_stake_spare_complete_credits_staked_spare_balance_on_success(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = U128::from(1_000);
+        contract.staking_exchange_rate = Some(U128::from(1));
+        context.switch_to_executor();
+        context.with_attached_deposit(NearToken::from_yoctonear(600), || {
+            contract.stake_spare(pool(), U128::from(600));
+        });
+
+        context.set_promise_results(vec![PromiseResult::Successful(vec![])]);
+        contract.on_stake_spare_complete();
+
+        assert!(!contract.spare_staking_locked);
+        assert_eq!(contract.pending_spare_stake_amount.0, 0);
+        assert_eq!(contract.staked_spare_balance.0, 600);
+        assert_eq!(contract.spare_balance.0, 400);
+    }
+
+    #[rstest]
+    fn on_stake_spare_complete_restores_spare_balance_on_failure(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = U128::from(1_000);
+        contract.staking_exchange_rate = Some(U128::from(1));
+        context.switch_to_executor();
+        context.with_attached_deposit(NearToken::from_yoctonear(600), || {
+            contract.stake_spare(pool(), U128::from(600));
+        });
+
+        context.set_promise_results(vec![PromiseResult::Failed]);
+        contract.on_stake_spare_complete();
+
+        assert!(!contract.spare_staking_locked);
+        assert_eq!(contract.pending_spare_stake_amount.0, 0);
+        assert_eq!(contract.staked_spare_balance.0, 0);
+        assert_eq!(contract.spare_balance.0, 1_000);
+
+        let (sequence, _) = contract.get_audit_head();
+        assert_eq!(sequence, 1);
+    }
+
+    #[rstest]
+    fn unstake_spare_locks_and_withdraws_on_success(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_staking_pool_id = Some(pool());
+        contract.staked_spare_balance = U128::from(600);
+
+        context.switch_to_executor();
+        contract.unstake_spare(U128::from(600));
+
+        assert!(contract.spare_staking_locked);
+        assert_eq!(contract.staked_spare_balance.0, 0);
+        assert_eq!(contract.pending_spare_stake_amount.0, 600);
+
+        context.set_promise_results(vec![PromiseResult::Successful(vec![])]);
+        contract.on_unstake_spare_complete();
+
+        assert!(!contract.spare_staking_locked);
+        assert_eq!(contract.spare_balance.0, 600);
+        assert_eq!(contract.staked_spare_balance.0, 0);
+        assert_eq!(contract.spare_staking_pool_id, None);
+    }
+
+    #[rstest]
+    fn unstake_spare_refuses_to_exceed_staked_balance(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_staking_pool_id = Some(pool());
+        contract.staked_spare_balance = U128::from(100);
+
+        context.switch_to_executor();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.unstake_spare(U128::from(600));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(contract.staked_spare_balance.0, 100);
+    }
+
+    #[rstest]
+    fn sync_staked_spare_balance_reconciles_reward_drift(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_staking_pool_id = Some(pool());
+        contract.staked_spare_balance = U128::from(600);
+
+        context.switch_to_executor();
+        contract.sync_staked_spare_balance();
+
+        assert!(contract.spare_staking_locked);
+
+        context.set_promise_results(vec![PromiseResult::Successful(
+            serde_json::to_vec(&U128::from(615)).unwrap(),
+        )]);
+        contract.on_sync_staked_spare_balance();
+
+        assert!(!contract.spare_staking_locked);
+        assert_eq!(contract.staked_spare_balance.0, 615);
+    }
+
+    #[rstest]
+    fn sync_staked_spare_balance_leaves_balance_untouched_on_failure(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_staking_pool_id = Some(pool());
+        contract.staked_spare_balance = U128::from(600);
+
+        context.switch_to_executor();
+        contract.sync_staked_spare_balance();
+
+        context.set_promise_results(vec![PromiseResult::Failed]);
+        contract.on_sync_staked_spare_balance();
+
+        assert!(!contract.spare_staking_locked);
+        assert_eq!(contract.staked_spare_balance.0, 600);
+    }
+
+    #[rstest]
+    fn sync_staked_spare_balance_requires_executor_role(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_staking_pool_id = Some(pool());
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.sync_staked_spare_balance();
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    fn claim_excludes_staked_amount_from_claimable(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        contract.create_grant_internal(&alice, DEFAULT_CLIFF, U128::from(1_000), None, None);
+        {
+            let grant = contract
+                .accounts
+                .get_mut(&alice)
+                .unwrap()
+                .grants
+                .get_mut(&DEFAULT_CLIFF)
+                .unwrap();
+            grant.staked_amount = U128::from(400);
+        }
+
+        context.switch_account(&alice);
+        context.set_block_timestamp_in_seconds(4_000);
+        contract.claim();
+
+        let grant = contract
+            .accounts
+            .get(&alice)
+            .unwrap()
+            .grants
+            .get(&DEFAULT_CLIFF)
+            .unwrap();
+        assert_eq!(grant.order_amount.0, 600);
+    }
+
+    #[rstest]
+    fn stake_spare_refuses_a_second_call_while_one_is_in_flight(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        contract.spare_balance = U128::from(1_000);
+        contract.staking_exchange_rate = Some(U128::from(1));
+        context.switch_to_executor();
+        context.with_attached_deposit(NearToken::from_yoctonear(600), || {
+            contract.stake_spare(pool(), U128::from(600));
+        });
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.stake_spare(pool(), U128::from(100));
+        }));
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use near_sdk::{env, json_types::U128, near, require, AccountId, NearToken, Promise};
+
+use crate::{Contract, ContractExt};
+
+/// Cost NEAR charges per byte of contract storage, mirrored from the order-book contract's
+/// storage-staking constant (1e19 yoctoNEAR / byte).
+pub const STORAGE_PRICE_PER_BYTE: NearToken = NearToken::from_yoctonear(10_000_000_000_000_000_000);
+
+/// Conservative fixed estimate of the bytes a fresh `Account` entry holding a single `Grant`
+/// adds to the `IterableMap`, mirrored in spirit from the lockup contract's fixed
+/// `MIN_BALANCE_FOR_STORAGE`: a constant floor rather than a measurement, since
+/// `storage_balance_bounds` is a view and can't run `issue`'s actual storage-diff measurement.
+const MIN_ACCOUNT_STORAGE_BYTES: u64 = 1_024;
+
+#[near(serializers = [json])]
+pub struct StorageBalance {
+    pub total: NearToken,
+}
+
+/// NEP-145 `storage_balance_bounds` response. `max` is `None` since an account can accumulate
+/// more than one grant over time and there's no fixed ceiling on how much storage it may need.
+#[near(serializers = [json])]
+pub struct StorageBalanceBounds {
+    pub min: NearToken,
+    pub max: Option<NearToken>,
+}
+
+/// StorageApi tracks NEAR deposited on behalf of a grantee to cover the storage bytes their
+/// `Account`/`Grant` entries add to the contract, so a large `issue` batch can never push the
+/// contract below its storage staking threshold.
+pub trait StorageApi {
+    /// Registers or tops up `account_id`'s (default: the caller's) storage balance with the
+    /// attached deposit.
+    fn storage_deposit(&mut self, account_id: Option<AccountId>) -> StorageBalance;
+
+    /// Withdraws `amount` (default: the full registered balance) of the caller's storage balance
+    /// back to them. Requires exactly 1 yoctoNEAR attached, per the storage management convention.
+    fn storage_withdraw(&mut self, amount: Option<NearToken>) -> StorageBalance;
+
+    /// Returns `account_id`'s currently registered storage balance, if any.
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+
+    /// Returns the NEP-145 bounds: `min` is enough to cover one `Account` entry holding a single
+    /// `Grant`, priced at `STORAGE_PRICE_PER_BYTE`.
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+
+    /// Plain-`U128` view of `account_id`'s registered storage balance in yoctoNEAR (`0` if
+    /// unregistered), mirroring `get_spare_balance`/`get_account`'s style of returning bare
+    /// values rather than the NEP-145-shaped `Option<StorageBalance>` `storage_balance_of` does.
+    fn get_storage_balance(&self, account_id: AccountId) -> U128;
+}
+
+#[near]
+impl StorageApi for Contract {
+    #[payable]
+    fn storage_deposit(&mut self, account_id: Option<AccountId>) -> StorageBalance {
+        let target = account_id.unwrap_or_else(env::predecessor_account_id);
+
+        let balance = self
+            .storage_deposits
+            .entry(target)
+            .or_insert(NearToken::from_yoctonear(0));
+        *balance = balance.saturating_add(env::attached_deposit());
+
+        StorageBalance { total: *balance }
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<NearToken>) -> StorageBalance {
+        require!(
+            env::attached_deposit() == NearToken::from_yoctonear(1),
+            "Requires exactly 1 yoctoNEAR of attached deposit"
+        );
+
+        let account_id = env::predecessor_account_id();
+        let registered = self
+            .storage_deposits
+            .get(&account_id)
+            .copied()
+            .unwrap_or(NearToken::from_yoctonear(0));
+        let amount = amount.unwrap_or(registered);
+
+        require!(
+            amount <= registered,
+            "Cannot withdraw more than the registered storage balance"
+        );
+
+        let remaining = registered.saturating_sub(amount);
+        self.storage_deposits.insert(account_id.clone(), remaining);
+
+        if amount.as_yoctonear() > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+
+        StorageBalance { total: remaining }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_deposits
+            .get(&account_id)
+            .map(|total| StorageBalance { total: *total })
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: NearToken::from_yoctonear(
+                u128::from(MIN_ACCOUNT_STORAGE_BYTES) * STORAGE_PRICE_PER_BYTE.as_yoctonear(),
+            ),
+            max: None,
+        }
+    }
+
+    fn get_storage_balance(&self, account_id: AccountId) -> U128 {
+        self.storage_deposits
+            .get(&account_id)
+            .copied()
+            .unwrap_or(NearToken::from_yoctonear(0))
+            .as_yoctonear()
+            .into()
+    }
+}
+
+impl Contract {
+    /// Charges the storage growth measured for `account_id` since `storage_usage_before`,
+    /// drawing first from `deposit_remaining` (the issuer's attached deposit for this batch) and
+    /// falling back to `account_id`'s own registered storage balance. Returns what's left of
+    /// `deposit_remaining` after this charge, or the shortfall message (as `Err`) if neither can
+    /// cover the measured cost — callers that already mutated state to produce the storage
+    /// growth being charged for (e.g. `GrantApi::issue_internal` inserting a grant) are
+    /// responsible for undoing that mutation before propagating the error as a panic.
+    pub(crate) fn charge_storage(
+        &mut self,
+        account_id: &AccountId,
+        storage_usage_before: u64,
+        deposit_remaining: NearToken,
+    ) -> Result<NearToken, String> {
+        let bytes_added = env::storage_usage().saturating_sub(storage_usage_before);
+        let cost = NearToken::from_yoctonear(
+            u128::from(bytes_added) * STORAGE_PRICE_PER_BYTE.as_yoctonear(),
+        );
+
+        if deposit_remaining >= cost {
+            return Ok(deposit_remaining.saturating_sub(cost));
+        }
+
+        let shortfall = cost.saturating_sub(deposit_remaining);
+        let balance = self
+            .storage_deposits
+            .entry(account_id.clone())
+            .or_insert(NearToken::from_yoctonear(0));
+
+        if *balance < shortfall {
+            return Err(format!(
+                "{} has insufficient storage balance: needs {} more yoctoNEAR",
+                account_id,
+                shortfall.saturating_sub(*balance).as_yoctonear()
+            ));
+        }
+
+        *balance = balance.saturating_sub(shortfall);
+
+        Ok(NearToken::from_yoctonear(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::{test_utils::accounts, AccountId, NearToken};
+    use rstest::*;
+
+    use crate::{storage::StorageApi, tests::context::TestContext, tests::fixtures::*, Contract};
+
+    #[rstest]
+    fn storage_deposit_accumulates_balance(mut context: TestContext, mut contract: Contract) {
+        context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.storage_deposit(Some(accounts(1)));
+        });
+        context.with_attached_deposit(NearToken::from_millinear(500), || {
+            contract.storage_deposit(Some(accounts(1)));
+        });
+
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(balance.total, NearToken::from_millinear(1_500));
+    }
+
+    #[rstest]
+    fn get_storage_balance_mirrors_storage_balance_of(
+        mut context: TestContext,
+        mut contract: Contract,
+    ) {
+        assert_eq!(contract.get_storage_balance(accounts(1)).0, 0);
+
+        context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.storage_deposit(Some(accounts(1)));
+        });
+
+        assert_eq!(
+            contract.get_storage_balance(accounts(1)).0,
+            NearToken::from_near(1).as_yoctonear()
+        );
+    }
+
+    #[rstest]
+    fn storage_balance_bounds_prices_the_minimum_floor_by_byte_cost(contract: Contract) {
+        let bounds = contract.storage_balance_bounds();
+
+        assert!(bounds.min.as_yoctonear() > 0);
+        assert!(bounds.max.is_none());
+    }
+
+    #[rstest]
+    fn storage_withdraw_returns_deposit(mut context: TestContext, mut contract: Contract) {
+        context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.storage_deposit(Some(accounts(1)));
+        });
+
+        context.switch_account(&accounts(1));
+        context.with_attached_deposit(NearToken::from_yoctonear(1), || {
+            contract.storage_withdraw(Some(NearToken::from_millinear(400)));
+        });
+
+        let balance = contract.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(balance.total, NearToken::from_millinear(600));
+    }
+
+    #[rstest]
+    fn issue_draws_from_attached_deposit_before_registered_balance(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        use crate::grant::GrantApi;
+
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_issuer();
+        context.with_attached_deposit(NearToken::from_near(1), || {
+            contract.issue(1_000, vec![(alice.clone(), 1_000.into(), None)], None);
+        });
+
+        assert!(contract.accounts.get(&alice).is_some());
+    }
+
+    #[rstest]
+    fn issue_fails_without_storage_funding(
+        mut context: TestContext,
+        mut contract: Contract,
+        alice: AccountId,
+    ) {
+        use std::panic::{self, AssertUnwindSafe};
+
+        use crate::grant::GrantApi;
+
+        contract.spare_balance = 10_000.into();
+
+        context.switch_to_issuer();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            contract.issue(1_000, vec![(alice.clone(), 1_000.into(), None)], None);
+        }));
+
+        assert!(result.is_err());
+        // issue_internal explicitly removes the grant it tentatively inserted to measure storage
+        // cost once charge_storage reports a shortfall, along with the account entry that removal
+        // leaves empty, so this holds even within the same
+        // process rather than relying on catch_unwind to have undone the earlier mutation.
+        assert!(contract.accounts.get(&alice).is_none());
+    }
+}
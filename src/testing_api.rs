@@ -11,6 +11,7 @@ use near_sdk_contract_tools::rbac::Rbac;
 
 pub const DEFAULT_CLIFF: u32 = 1_000;
 pub const DEFAULT_UNLOCK: u32 = 2_000;
+pub const DEFAULT_TOKEN_DECIMALS: u8 = 18;
 
 pub fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
     let mut builder = VMContextBuilder::new();
@@ -32,7 +33,13 @@ pub fn set_predecessor_with_time(account: &AccountId, timestamp: u64) {
 }
 
 pub fn init_contract_with_spare(spare_balance: u128) -> Contract {
-    let mut contract = Contract::new(accounts(0), DEFAULT_CLIFF, DEFAULT_UNLOCK, accounts(0));
+    let mut contract = Contract::new(
+        accounts(0),
+        DEFAULT_CLIFF,
+        DEFAULT_UNLOCK,
+        accounts(0),
+        DEFAULT_TOKEN_DECIMALS,
+    );
 
     contract.spare_balance = spare_balance.into();
     contract.add_role(&accounts(0), &Role::Executor);
@@ -43,7 +50,7 @@ pub fn init_contract_with_spare(spare_balance: u128) -> Contract {
 
 pub fn init_contract_with_grant(total_amount: U128) -> Contract {
     let mut contract = init_contract_with_spare(total_amount.0);
-    contract.create_grant_internal(&accounts(1), DEFAULT_CLIFF, total_amount, None);
+    contract.create_grant_internal(&accounts(1), DEFAULT_CLIFF, total_amount, None, None);
 
     contract
 }
@@ -1,6 +1,8 @@
 #![cfg(test)]
 
-use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId, Gas, PromiseResult};
+use near_sdk::{
+    test_utils::VMContextBuilder, testing_env, AccountId, Gas, NearToken, PromiseResult, PublicKey,
+};
 
 use super::fixtures::{executor, issuer};
 
@@ -33,6 +35,10 @@ impl TestContext {
             .build());
     }
 
+    pub fn set_block_height(&mut self, height: u64) {
+        testing_env!(self.builder.block_height(height).build());
+    }
+
     pub fn switch_account(&mut self, account_id: &AccountId) {
         testing_env!(self
             .builder
@@ -49,6 +55,19 @@ impl TestContext {
         self.switch_account(&executor());
     }
 
+    /// Simulates a call made through a `DelegationApi::register_claim_key` access key: such a
+    /// key lives on the contract's own account, so both `signer_account_id` and
+    /// `predecessor_account_id` are the contract itself, and `claim` must resolve the grantee
+    /// from `signer_account_pk` instead.
+    pub fn switch_to_claim_key(&mut self, public_key: PublicKey) {
+        testing_env!(self
+            .builder
+            .signer_account_id(contract_account_id())
+            .predecessor_account_id(contract_account_id())
+            .signer_account_pk(public_key)
+            .build())
+    }
+
     pub fn set_promise_results(&mut self, results: Vec<PromiseResult>) {
         testing_env!(
             self.builder.build(),
@@ -71,4 +90,20 @@ impl TestContext {
 
         result
     }
+
+    pub fn with_attached_deposit<F, R>(&mut self, deposit: NearToken, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        testing_env!(self.builder.attached_deposit(deposit).build());
+
+        let result = f();
+
+        testing_env!(self
+            .builder
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .build());
+
+        result
+    }
 }
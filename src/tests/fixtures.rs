@@ -8,6 +8,7 @@ use super::context::TestContext;
 
 pub const DEFAULT_CLIFF_DURATION: u32 = 1_000;
 pub const DEFAULT_VESTING_DURATION: u32 = 2_000;
+pub const DEFAULT_TOKEN_DECIMALS: u8 = 18;
 
 #[fixture]
 pub fn context() -> TestContext {
@@ -53,7 +54,13 @@ pub fn contract(
     issuer: AccountId,
     executor: AccountId,
 ) -> Contract {
-    let mut contract = Contract::new(token, cliff_duration, vesting_duration, owner);
+    let mut contract = Contract::new(
+        token,
+        cliff_duration,
+        vesting_duration,
+        owner,
+        DEFAULT_TOKEN_DECIMALS,
+    );
 
     contract.add_role(&executor, &Role::Executor);
     contract.add_role(&issuer, &Role::Issuer);
@@ -0,0 +1,4 @@
+#![cfg(test)]
+
+pub mod context;
+pub mod fixtures;
@@ -1,5 +1,70 @@
 use near_sdk::env::panic_str;
 
+/// Computes `amount * elapsed`, tracked as a 256-bit value (`hi`, `lo`), so the
+/// intermediate product can't silently overflow `u128` before we divide by `duration`.
+fn widening_mul(amount: u128, elapsed: u128) -> (u128, u128) {
+    let a_lo = amount & u128::from(u64::MAX);
+    let a_hi = amount >> 64;
+    let b_lo = elapsed & u128::from(u64::MAX);
+    let b_hi = elapsed >> 64;
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let (mid, carry) = hi_lo.overflowing_add(lo_hi);
+    let mid_hi = mid >> 64;
+    let mid_lo = mid & u128::from(u64::MAX);
+
+    let (lo, carry_lo) = lo_lo.overflowing_add(mid_lo << 64);
+    let hi = hi_hi
+        .wrapping_add(mid_hi)
+        .wrapping_add(u128::from(carry) << 64)
+        .wrapping_add(u128::from(carry_lo));
+
+    (hi, lo)
+}
+
+/// Divides the 256-bit value `(hi, lo)` by `divisor`, panicking if the quotient doesn't fit in `u128`.
+fn div_256_by_128(hi: u128, lo: u128, divisor: u128) -> u128 {
+    let mut remainder: u128 = 0;
+    let mut quotient_hi: u128 = 0;
+    let mut quotient_lo: u128 = 0;
+
+    for i in (0..128).rev() {
+        let bit = (hi >> i) & 1;
+        let top_bit = remainder >> 127;
+        remainder = (remainder << 1) | bit;
+
+        if top_bit == 1 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient_hi = (quotient_hi << 1) | 1;
+        } else {
+            quotient_hi <<= 1;
+        }
+    }
+
+    for i in (0..128).rev() {
+        let bit = (lo >> i) & 1;
+        let top_bit = remainder >> 127;
+        remainder = (remainder << 1) | bit;
+
+        if top_bit == 1 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient_lo = (quotient_lo << 1) | 1;
+        } else {
+            quotient_lo <<= 1;
+        }
+    }
+
+    if quotient_hi != 0 {
+        panic_str("Vested amount overflowed u128");
+    }
+
+    quotient_lo
+}
+
 pub(crate) fn calculate_vested_amount(
     now: u32,
     cliff_end: u32,
@@ -14,13 +79,12 @@ pub(crate) fn calculate_vested_amount(
         return amount;
     }
 
-    let vest_per_second = amount / u128::from(vesting_end - cliff_end);
-    let seconds_ellapsed = (now - cliff_end) as u128;
+    let duration = u128::from(vesting_end - cliff_end);
+    let elapsed = u128::from(now - cliff_end);
+
+    let (hi, lo) = widening_mul(amount, elapsed);
 
-    return vest_per_second
-        .checked_mul(seconds_ellapsed)
-        .unwrap_or_else(|| panic_str("Failed to multiply."))
-        .into();
+    div_256_by_128(hi, lo, duration)
 }
 
 #[cfg(test)]
@@ -61,4 +125,34 @@ mod tests {
             calculate_vested_amount(cliff_end + 99_999, cliff_end, vesting_end, amount)
         );
     }
+
+    #[test]
+    fn test_vesting_does_not_strand_trailing_units() {
+        // amount not evenly divisible by duration: the old floor-then-multiply path
+        // stranded the remainder every second; amount * elapsed / duration should not.
+        let amount = 1_000_000_000_000_000_000_000_000_001u128; // 1e24 + 1
+        let cliff_end = 0;
+        let vesting_end = 3;
+
+        let total: u128 = (0..=vesting_end)
+            .map(|now| calculate_vested_amount(now, cliff_end, vesting_end, amount))
+            .last()
+            .unwrap();
+        assert_eq!(total, amount);
+
+        // Elapsed 1 of 3 rounds down to floor(amount/3).
+        let vested_one_third = calculate_vested_amount(1, cliff_end, vesting_end, amount);
+        assert_eq!(vested_one_third, amount / 3);
+    }
+
+    #[test]
+    fn test_vesting_handles_large_amount_without_overflow() {
+        // ~1e33, close to the max a 24-decimal token amount can reach.
+        let amount = 1_000_000_000_000_000_000_000_000_000_000_000u128;
+        let cliff_end = 0;
+        let vesting_end = 100_000_000; // ~3 years in seconds
+
+        let vested = calculate_vested_amount(vesting_end / 2, cliff_end, vesting_end, amount);
+        assert_eq!(vested, amount / 2);
+    }
 }